@@ -0,0 +1,224 @@
+/*
+ * Copyright (c) 2023, networkException <git@nwex.de>
+ *
+ * SPDX-License-Identifier: BSD-2-Clause OR MIT
+ */
+
+use std::io;
+use std::mem;
+use std::os::unix::io::RawFd;
+use std::ptr;
+
+// NOTE: The kernel refuses to pass more file descriptors in a single message (SCM_MAX_FD).
+const MAX_FDS: usize = 253;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const SEND_FLAGS: libc::c_int = libc::MSG_NOSIGNAL;
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+const SEND_FLAGS: libc::c_int = 0;
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"))]
+const RECV_FLAGS: libc::c_int = libc::MSG_CMSG_CLOEXEC;
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd")))]
+const RECV_FLAGS: libc::c_int = 0;
+
+fn control_space(data_size: usize) -> usize {
+    unsafe { libc::CMSG_SPACE(data_size as u32) as usize }
+}
+
+// Control messages need to be aligned like cmsghdr, which a plain Vec<u8> doesn't guarantee.
+fn control_buffer(space: usize) -> Vec<u64> {
+    vec![0; space.div_ceil(mem::size_of::<u64>())]
+}
+
+// NOTE: Closes the file descriptors of SCM_RIGHTS messages nobody asked for, as they were
+//       already installed into the process by the kernel.
+unsafe fn close_fds(message: &libc::msghdr) {
+    let mut header = libc::CMSG_FIRSTHDR(message);
+
+    while !header.is_null() {
+        if (*header).cmsg_level == libc::SOL_SOCKET && (*header).cmsg_type == libc::SCM_RIGHTS {
+            let data = libc::CMSG_DATA(header);
+            let data_size = (*header).cmsg_len as usize - (data as usize - header as usize);
+
+            for index in 0..data_size / mem::size_of::<RawFd>() {
+                libc::close(ptr::read_unaligned(data.cast::<RawFd>().add(index)));
+            }
+        }
+
+        header = libc::CMSG_NXTHDR(message, header);
+    }
+}
+
+fn check(result: isize) -> io::Result<usize> {
+    if result < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(result as usize)
+    }
+}
+
+pub(crate) fn send_with_fds(socket: RawFd, buf: &[u8], fds: &[RawFd]) -> io::Result<usize> {
+    if fds.len() > MAX_FDS {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Cannot pass more than 253 file descriptors in a single message.",
+        ))
+    }
+
+    let mut iovec = libc::iovec {
+        iov_base: buf.as_ptr() as *mut libc::c_void,
+        iov_len: buf.len(),
+    };
+
+    let data_size = mem::size_of_val(fds);
+    let mut control = control_buffer(control_space(data_size));
+
+    let mut message: libc::msghdr = unsafe { mem::zeroed() };
+    message.msg_iov = &mut iovec;
+    message.msg_iovlen = 1;
+
+    if !fds.is_empty() {
+        message.msg_control = control.as_mut_ptr().cast();
+        message.msg_controllen = (control.len() * mem::size_of::<u64>()) as _;
+
+        unsafe {
+            let header = libc::CMSG_FIRSTHDR(&message);
+            (*header).cmsg_level = libc::SOL_SOCKET;
+            (*header).cmsg_type = libc::SCM_RIGHTS;
+            (*header).cmsg_len = libc::CMSG_LEN(data_size as u32) as _;
+
+            ptr::copy_nonoverlapping(fds.as_ptr().cast::<u8>(), libc::CMSG_DATA(header), data_size);
+        }
+    }
+
+    check(unsafe { libc::sendmsg(socket, &message, SEND_FLAGS) })
+}
+
+pub(crate) fn recv_with_fds(socket: RawFd, buf: &mut [u8], fds: &mut Vec<RawFd>) -> io::Result<usize> {
+    let mut iovec = libc::iovec {
+        iov_base: buf.as_mut_ptr().cast(),
+        iov_len: buf.len(),
+    };
+
+    // NOTE: With SO_PASSCRED enabled, Linux attaches the credentials of the sender to every
+    //       message, which would truncate the file descriptors without room for them.
+    #[cfg(target_os = "linux")]
+    let mut control = control_buffer(control_space(MAX_FDS * mem::size_of::<RawFd>()) + control_space(mem::size_of::<libc::ucred>()));
+    #[cfg(not(target_os = "linux"))]
+    let mut control = control_buffer(control_space(MAX_FDS * mem::size_of::<RawFd>()));
+
+    let mut message: libc::msghdr = unsafe { mem::zeroed() };
+    message.msg_iov = &mut iovec;
+    message.msg_iovlen = 1;
+    message.msg_control = control.as_mut_ptr().cast();
+    message.msg_controllen = (control.len() * mem::size_of::<u64>()) as _;
+
+    let size = check(unsafe { libc::recvmsg(socket, &mut message, RECV_FLAGS) })?;
+
+    let mut received = Vec::new();
+
+    unsafe {
+        let mut header = libc::CMSG_FIRSTHDR(&message);
+
+        while !header.is_null() {
+            if (*header).cmsg_level == libc::SOL_SOCKET && (*header).cmsg_type == libc::SCM_RIGHTS {
+                let data = libc::CMSG_DATA(header);
+                let data_size = (*header).cmsg_len as usize - (data as usize - header as usize);
+
+                for index in 0..data_size / mem::size_of::<RawFd>() {
+                    received.push(ptr::read_unaligned(data.cast::<RawFd>().add(index)));
+                }
+            }
+
+            header = libc::CMSG_NXTHDR(&message, header);
+        }
+    }
+
+    if message.msg_flags & libc::MSG_CTRUNC != 0 {
+        for fd in received {
+            unsafe { libc::close(fd) };
+        }
+
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Received control message was truncated.",
+        ))
+    }
+
+    fds.extend(received);
+
+    Ok(size)
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn send_with_creds(socket: RawFd, buf: &[u8], creds: &libc::ucred) -> io::Result<usize> {
+    let mut iovec = libc::iovec {
+        iov_base: buf.as_ptr() as *mut libc::c_void,
+        iov_len: buf.len(),
+    };
+
+    let data_size = mem::size_of::<libc::ucred>();
+    let mut control = control_buffer(control_space(data_size));
+
+    let mut message: libc::msghdr = unsafe { mem::zeroed() };
+    message.msg_iov = &mut iovec;
+    message.msg_iovlen = 1;
+    message.msg_control = control.as_mut_ptr().cast();
+    message.msg_controllen = (control.len() * mem::size_of::<u64>()) as _;
+
+    unsafe {
+        let header = libc::CMSG_FIRSTHDR(&message);
+        (*header).cmsg_level = libc::SOL_SOCKET;
+        (*header).cmsg_type = libc::SCM_CREDENTIALS;
+        (*header).cmsg_len = libc::CMSG_LEN(data_size as u32) as _;
+
+        ptr::write_unaligned(libc::CMSG_DATA(header).cast::<libc::ucred>(), *creds);
+    }
+
+    check(unsafe { libc::sendmsg(socket, &message, SEND_FLAGS) })
+}
+
+// NOTE: Credentials are only received while SO_PASSCRED is enabled on the receiving socket.
+#[cfg(target_os = "linux")]
+pub(crate) fn recv_with_creds(socket: RawFd, buf: &mut [u8]) -> io::Result<(usize, Option<libc::ucred>)> {
+    let mut iovec = libc::iovec {
+        iov_base: buf.as_mut_ptr().cast(),
+        iov_len: buf.len(),
+    };
+
+    let mut control = control_buffer(control_space(mem::size_of::<libc::ucred>()));
+
+    let mut message: libc::msghdr = unsafe { mem::zeroed() };
+    message.msg_iov = &mut iovec;
+    message.msg_iovlen = 1;
+    message.msg_control = control.as_mut_ptr().cast();
+    message.msg_controllen = (control.len() * mem::size_of::<u64>()) as _;
+
+    let size = check(unsafe { libc::recvmsg(socket, &mut message, RECV_FLAGS) })?;
+
+    unsafe { close_fds(&message) };
+
+    if message.msg_flags & libc::MSG_CTRUNC != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Received control message was truncated.",
+        ))
+    }
+
+    let mut creds = None;
+
+    unsafe {
+        let mut header = libc::CMSG_FIRSTHDR(&message);
+
+        while !header.is_null() {
+            if (*header).cmsg_level == libc::SOL_SOCKET && (*header).cmsg_type == libc::SCM_CREDENTIALS {
+                creds = Some(ptr::read_unaligned(libc::CMSG_DATA(header).cast::<libc::ucred>()));
+            }
+
+            header = libc::CMSG_NXTHDR(&message, header);
+        }
+    }
+
+    Ok((size, creds))
+}
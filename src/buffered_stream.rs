@@ -0,0 +1,148 @@
+/*
+ * Copyright (c) 2023, networkException <git@nwex.de>
+ *
+ * SPDX-License-Identifier: BSD-2-Clause OR MIT
+ */
+
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncBufRead, AsyncRead, AsyncWrite, BufStream, ReadBuf};
+
+use crate::Stream;
+
+// NOTE: Writes are only sent once the write buffer is full or the stream is flushed or
+//       shut down, so a request has to be flushed before waiting for its response.
+#[derive(Debug)]
+pub struct BufferedStream {
+    inner: BufStream<Stream>,
+}
+
+impl BufferedStream {
+    pub fn new(stream: Stream) -> BufferedStream {
+        BufferedStream { inner: BufStream::new(stream) }
+    }
+
+    pub fn with_capacity(stream: Stream, read_capacity: usize, write_capacity: usize) -> BufferedStream {
+        BufferedStream { inner: BufStream::with_capacity(read_capacity, write_capacity, stream) }
+    }
+
+    pub fn get_ref(&self) -> &Stream {
+        self.inner.get_ref()
+    }
+
+    pub fn get_mut(&mut self) -> &mut Stream {
+        self.inner.get_mut()
+    }
+
+    // NOTE: Data that was buffered but not read or written yet is lost.
+    pub fn into_inner(self) -> Stream {
+        self.inner.into_inner()
+    }
+}
+
+impl From<Stream> for BufferedStream {
+    fn from(stream: Stream) -> BufferedStream {
+        BufferedStream::new(stream)
+    }
+}
+
+impl AsyncRead for BufferedStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut Pin::into_inner(self).inner).poll_read(cx, buf)
+    }
+}
+
+impl AsyncBufRead for BufferedStream {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        Pin::new(&mut Pin::into_inner(self).inner).poll_fill_buf(cx)
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        Pin::new(&mut Pin::into_inner(self).inner).consume(amt)
+    }
+}
+
+impl AsyncWrite for BufferedStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut Pin::into_inner(self).inner).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut Pin::into_inner(self).inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut Pin::into_inner(self).inner).poll_flush(cx)
+    }
+
+    // NOTE: Flushes the write buffer before shutting down the inner stream.
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut Pin::into_inner(self).inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt};
+    use tokio::time;
+
+    use crate::test_util;
+
+    #[tokio::test]
+    async fn buffers_reads_and_writes() {
+        let (stream, mut other) = test_util::tcp_pair().await;
+        let mut buffered = stream.buffered_with_capacity(16, 16);
+
+        buffered.write_all(b"request\n").await.unwrap();
+
+        // NOTE: Nothing is sent before flushing.
+        assert!(time::timeout(Duration::from_millis(10), other.read(&mut [0; 1])).await.is_err());
+
+        buffered.flush().await.unwrap();
+
+        let mut buf = [0; 8];
+
+        other.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"request\n");
+
+        other.write_all(b"first\nsecond\n").await.unwrap();
+
+        let mut line = String::new();
+
+        buffered.read_line(&mut line).await.unwrap();
+        assert_eq!(line, "first\n");
+
+        line.clear();
+        buffered.read_line(&mut line).await.unwrap();
+        assert_eq!(line, "second\n");
+
+        // NOTE: Shutting down flushes pending writes first.
+        buffered.write_all(b"bye").await.unwrap();
+        buffered.shutdown().await.unwrap();
+
+        let mut rest = Vec::new();
+
+        other.read_to_end(&mut rest).await.unwrap();
+        assert_eq!(rest, b"bye");
+    }
+}
@@ -0,0 +1,56 @@
+/*
+ * Copyright (c) 2023, networkException <git@nwex.de>
+ *
+ * SPDX-License-Identifier: BSD-2-Clause OR MIT
+ */
+
+use std::io;
+
+use tokio_util::codec::{Decoder, Framed};
+
+use crate::{Listener, SocketAddr, Stream};
+
+impl Listener {
+    // Accepts a connection and wraps it in a Framed using the given codec. Codecs
+    // usually carry per connection state, pass a fresh or cloned codec for every call.
+    pub async fn accept_framed<C: Decoder>(&self, codec: C) -> io::Result<(Framed<Stream, C>, SocketAddr)> {
+        let (stream, socket_addr) = self.accept().await?;
+
+        Ok((Framed::new(stream, codec), socket_addr))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future;
+    use std::pin::Pin;
+
+    use futures_core::Stream as _;
+    use tokio::io::AsyncWriteExt;
+    use tokio_util::codec::LinesCodec;
+
+    use super::*;
+    use crate::test_util;
+
+    #[tokio::test]
+    async fn accept_framed() {
+        let (listener, local_addr) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+        let named_socket_addr = local_addr.to_named_socket_addr().unwrap();
+
+        let (client, accepted) = tokio::join!(Stream::connect(&named_socket_addr), listener.accept_framed(LinesCodec::new()));
+        let (mut client, (mut framed, socket_addr)) = (client.unwrap(), accepted.unwrap());
+
+        assert_eq!(socket_addr, client.local_addr().unwrap());
+
+        client.write_all(b"hello\nworld\n").await.unwrap();
+        client.shutdown().await.unwrap();
+
+        let mut lines = Vec::new();
+
+        while let Some(line) = future::poll_fn(|cx| Pin::new(&mut framed).poll_next(cx)).await {
+            lines.push(line.unwrap());
+        }
+
+        assert_eq!(lines, ["hello", "world"]);
+    }
+}
@@ -0,0 +1,228 @@
+/*
+ * Copyright (c) 2023, networkException <git@nwex.de>
+ *
+ * SPDX-License-Identifier: BSD-2-Clause OR MIT
+ */
+
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::io;
+use std::mem;
+use std::net;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use socket2::{Domain, SockAddr, Socket, Type};
+use tokio::net::TcpStream;
+
+#[cfg(unix)]
+use {std::{os::unix::io::OwnedFd, path::Path}, tokio::net::UnixStream};
+
+use crate::{NamedSocketAddr, Stream};
+#[cfg(windows)]
+use crate::named_pipe;
+
+#[cfg(all(feature = "vsock", target_os = "linux"))]
+use tokio_vsock::{VsockAddr, VsockStream};
+
+// NOTE: A future that can be named and stored, unlike the ones returned by tokio's connect
+//       functions. TCP connects are started without blocking and complete once the socket
+//       becomes writable, Unix connects complete when first polled.
+pub struct Connect {
+    state: State,
+}
+
+enum State {
+    // NOTE: Nothing happens until the first poll, registering with the tokio reactor needs
+    //       to happen inside the runtime.
+    Start(NamedSocketAddr),
+    Connecting(TcpStream),
+    // NOTE: Named pipes and vsock are connected to using their own futures.
+    #[cfg(any(windows, all(feature = "vsock", target_os = "linux")))]
+    Other(Pin<Box<dyn Future<Output = io::Result<Stream>> + Send>>),
+    Done,
+}
+
+impl Connect {
+    pub(crate) fn new(named_socket_addr: NamedSocketAddr) -> Connect {
+        Connect {
+            state: State::Start(named_socket_addr),
+        }
+    }
+}
+
+impl Future for Connect {
+    type Output = io::Result<Stream>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<Stream>> {
+        let state = &mut Pin::into_inner(self).state;
+
+        loop {
+            match mem::replace(state, State::Done) {
+                State::Start(named_socket_addr) => match named_socket_addr {
+                    NamedSocketAddr::Inet(inet_socket_addr) => *state = State::Connecting(start_tcp(inet_socket_addr)?),
+                    #[cfg(unix)]
+                    NamedSocketAddr::Unix(ref path) => return Poll::Ready(connect_unix(&named_socket_addr, path)),
+                    #[cfg(not(unix))]
+                    NamedSocketAddr::Unix(_) => return Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::Unsupported,
+                        "Cannot connect to a unix socket on a non unix system.",
+                    ))),
+                    #[cfg(windows)]
+                    NamedSocketAddr::Pipe(pipe_name) => *state = State::Other(Box::pin(async move {
+                        named_pipe::connect(&pipe_name).await.map(Stream::PipeClient)
+                    })),
+                    #[cfg(all(feature = "vsock", target_os = "linux"))]
+                    NamedSocketAddr::Vsock { cid, port } => *state = State::Other(Box::pin(async move {
+                        VsockStream::connect(VsockAddr::new(cid, port)).await.map(Stream::Vsock)
+                    })),
+                },
+                State::Connecting(tcp_stream) => {
+                    if let Poll::Ready(result) = tcp_stream.poll_write_ready(cx) {
+                        result?;
+
+                        // NOTE: The socket also becomes writable if connecting failed.
+                        return Poll::Ready(match tcp_stream.take_error()? {
+                            Some(error) => Err(error),
+                            None => Ok(Stream::Tcp(tcp_stream)),
+                        })
+                    }
+
+                    *state = State::Connecting(tcp_stream);
+
+                    return Poll::Pending
+                }
+                #[cfg(any(windows, all(feature = "vsock", target_os = "linux")))]
+                State::Other(mut future) => {
+                    if let Poll::Ready(result) = future.as_mut().poll(cx) {
+                        return Poll::Ready(result)
+                    }
+
+                    *state = State::Other(future);
+
+                    return Poll::Pending
+                }
+                State::Done => return Poll::Ready(Err(io::Error::other("Cannot poll a connect future after it completed."))),
+            }
+        }
+    }
+}
+
+impl Debug for Connect {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let state = match self.state {
+            State::Start(_) => "start",
+            State::Connecting(_) => "connecting",
+            #[cfg(any(windows, all(feature = "vsock", target_os = "linux")))]
+            State::Other(_) => "other",
+            State::Done => "done",
+        };
+
+        f.debug_struct("Connect").field("state", &state).finish()
+    }
+}
+
+fn start_tcp(inet_socket_addr: net::SocketAddr) -> io::Result<TcpStream> {
+    let socket = Socket::new(Domain::for_address(inet_socket_addr), Type::STREAM, None)?;
+    socket.set_nonblocking(true)?;
+
+    match socket.connect(&SockAddr::from(inet_socket_addr)) {
+        Ok(()) => {}
+        Err(error) if in_progress(&error) => {}
+        Err(error) => return Err(error),
+    }
+
+    TcpStream::from_std(net::TcpStream::from(socket))
+}
+
+fn in_progress(error: &io::Error) -> bool {
+    #[cfg(unix)]
+    if error.raw_os_error() == Some(libc::EINPROGRESS) {
+        return true
+    }
+
+    // NOTE: Windows reports pending connections as WSAEWOULDBLOCK.
+    error.kind() == io::ErrorKind::WouldBlock
+}
+
+// NOTE: Unix sockets connect immediately, while the backlog of the listener is full this
+//       fails with io::ErrorKind::WouldBlock like tokio's UnixStream::connect does.
+#[cfg(unix)]
+fn connect_unix(named_socket_addr: &NamedSocketAddr, path: &Path) -> io::Result<Stream> {
+    if let Some(fd) = named_socket_addr.inherited_fd() {
+        return Stream::from_inherited(fd)
+    }
+
+    let socket = Socket::new(Domain::UNIX, Type::STREAM, None)?;
+    socket.set_nonblocking(true)?;
+    socket.connect(&SockAddr::unix(path)?)?;
+
+    UnixStream::from_std(OwnedFd::from(socket).into()).map(Stream::Unix)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::poll_fn;
+    #[cfg(unix)]
+    use std::task::Waker;
+
+    use super::*;
+    use crate::{test_util, Listener};
+
+    #[tokio::test]
+    async fn storable_future() {
+        let (listener, local_addr) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+
+        // NOTE: Stored in a struct field to make sure the type can be named.
+        struct Pending {
+            connect: Connect,
+        }
+
+        let pending = Pending { connect: Stream::connect_future(&local_addr.to_named_socket_addr().unwrap()) };
+        let (client, accepted) = tokio::join!(pending.connect, listener.accept());
+
+        assert_eq!(client.unwrap().local_addr().unwrap(), accepted.unwrap().1);
+
+        let error = Stream::connect_future(&test_util::unused_tcp_addr().await).await.unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::ConnectionRefused);
+    }
+
+    #[tokio::test]
+    async fn tcp_waits_for_writable() {
+        let (listener, local_addr) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+        let mut connect = Stream::connect_future(&local_addr.to_named_socket_addr().unwrap());
+        let mut polls = 0;
+
+        let client = poll_fn(|cx| {
+            polls += 1;
+            Pin::new(&mut connect).poll(cx)
+        }).await.unwrap();
+
+        // NOTE: The first poll starts connecting, the second runs once the socket is writable
+        //       instead of the future being polled in a loop.
+        assert!(polls <= 2, "polled {} times", polls);
+        assert!(matches!(client, Stream::Tcp(_)));
+        assert_eq!(client.local_addr().unwrap(), listener.accept().await.unwrap().1);
+        assert!(poll_fn(|cx| Pin::new(&mut connect).poll(cx)).await.is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn unix_connects_when_first_polled() {
+        let path = test_util::temp_path("connect.sock");
+        let listener = Listener::bind(&NamedSocketAddr::unix(&path)).await.unwrap();
+        let mut connect = Stream::connect_future(&NamedSocketAddr::unix(&path));
+
+        let client = match Pin::new(&mut connect).poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(result) => result.unwrap(),
+            Poll::Pending => panic!("Connecting to a unix socket should not be pending"),
+        };
+
+        assert!(matches!(client, Stream::Unix(_)));
+        assert_eq!(client.peer_addr().unwrap(), crate::SocketAddr::unix(&path));
+        listener.accept().await.unwrap();
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,68 @@
+/*
+ * Copyright (c) 2023, networkException <git@nwex.de>
+ *
+ * SPDX-License-Identifier: BSD-2-Clause OR MIT
+ */
+
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+
+use crate::{Listener, MultiListener, SharedListener, SocketAddr, Stream};
+
+pub type AcceptFuture<'a> = Pin<Box<dyn Future<Output = io::Result<(Stream, SocketAddr)>> + Send + 'a>>;
+pub type BoxedConnectionSource = Box<dyn ConnectionSource>;
+
+// NOTE: Returns a boxed future so the trait can be used as a trait object, e.g. by server
+//       code that accepts from any kind of listener.
+pub trait ConnectionSource: Send + Sync {
+    fn accept(&self) -> AcceptFuture<'_>;
+}
+
+impl ConnectionSource for Listener {
+    fn accept(&self) -> AcceptFuture<'_> {
+        Box::pin(Listener::accept(self))
+    }
+}
+
+impl ConnectionSource for MultiListener {
+    fn accept(&self) -> AcceptFuture<'_> {
+        Box::pin(MultiListener::accept(self))
+    }
+}
+
+impl ConnectionSource for SharedListener {
+    fn accept(&self) -> AcceptFuture<'_> {
+        Box::pin(SharedListener::accept(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    #[tokio::test]
+    async fn accepts_through_trait_objects() {
+        let mut sources: Vec<(BoxedConnectionSource, SocketAddr)> = Vec::new();
+
+        let (listener, local_addr) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+        sources.push((Box::new(listener), local_addr));
+
+        let (listener, local_addr) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+        sources.push((Box::new(SharedListener::new(listener)), local_addr));
+
+        let (listener, local_addr) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+        sources.push((Box::new(MultiListener::new(vec![listener])), local_addr));
+
+        for (source, local_addr) in sources {
+            let named_socket_addr = local_addr.to_named_socket_addr().unwrap();
+
+            // NOTE: The futures are Send, so accepting can happen on a spawned task.
+            let accept = tokio::spawn(async move { source.accept().await.map(|(_, socket_addr)| socket_addr) });
+            let client = Stream::connect(&named_socket_addr).await.unwrap();
+
+            assert_eq!(accept.await.unwrap().unwrap(), client.local_addr().unwrap());
+        }
+    }
+}
@@ -0,0 +1,154 @@
+/*
+ * Copyright (c) 2023, networkException <git@nwex.de>
+ *
+ * SPDX-License-Identifier: BSD-2-Clause OR MIT
+ */
+
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::Stream;
+
+// NOTE: Only bytes that were actually transferred by the inner stream are counted,
+//       so partial reads and writes are accounted for correctly.
+#[derive(Debug)]
+pub struct CountingStream {
+    inner: Stream,
+    bytes_read: AtomicU64,
+    bytes_written: AtomicU64,
+}
+
+impl CountingStream {
+    pub fn new(stream: Stream) -> CountingStream {
+        CountingStream {
+            inner: stream,
+            bytes_read: AtomicU64::new(0),
+            bytes_written: AtomicU64::new(0),
+        }
+    }
+
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read.load(Ordering::Relaxed)
+    }
+
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written.load(Ordering::Relaxed)
+    }
+
+    pub fn get_ref(&self) -> &Stream {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut Stream {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> Stream {
+        self.inner
+    }
+}
+
+impl From<Stream> for CountingStream {
+    fn from(stream: Stream) -> CountingStream {
+        CountingStream::new(stream)
+    }
+}
+
+impl AsyncRead for CountingStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = Pin::into_inner(self);
+        let filled = buf.filled().len();
+
+        let poll = Pin::new(&mut this.inner).poll_read(cx, buf);
+
+        if let Poll::Ready(Ok(())) = poll {
+            this.bytes_read.fetch_add((buf.filled().len() - filled) as u64, Ordering::Relaxed);
+        }
+
+        poll
+    }
+}
+
+impl AsyncWrite for CountingStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = Pin::into_inner(self);
+        let poll = Pin::new(&mut this.inner).poll_write(cx, buf);
+
+        if let Poll::Ready(Ok(written)) = poll {
+            this.bytes_written.fetch_add(written as u64, Ordering::Relaxed);
+        }
+
+        poll
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let this = Pin::into_inner(self);
+        let poll = Pin::new(&mut this.inner).poll_write_vectored(cx, bufs);
+
+        if let Poll::Ready(Ok(written)) = poll {
+            this.bytes_written.fetch_add(written as u64, Ordering::Relaxed);
+        }
+
+        poll
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut Pin::into_inner(self).inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut Pin::into_inner(self).inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::IoSlice;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+    use crate::test_util;
+
+    #[tokio::test]
+    async fn counts_transferred_bytes() {
+        let (a, b) = test_util::tcp_pair().await;
+        let (mut a, mut b) = (a.with_counters(), CountingStream::from(b));
+
+        a.write_all(b"partial").await.unwrap();
+        assert_eq!(a.bytes_written(), 7);
+
+        let mut buf = [0; 8];
+
+        b.read_exact(&mut buf[..7]).await.unwrap();
+        assert_eq!(b.bytes_read(), 7);
+
+        // NOTE: Only the bytes actually written are counted, not the size of the buffers.
+        let written = a.write_vectored(&[IoSlice::new(b"ab"), IoSlice::new(b"c")]).await.unwrap();
+
+        b.read_exact(&mut buf[..written]).await.unwrap();
+
+        assert_eq!((a.bytes_written(), a.bytes_read()), (7 + written as u64, 0));
+        assert_eq!((b.bytes_read(), b.bytes_written()), (7 + written as u64, 0));
+    }
+}
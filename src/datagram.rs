@@ -0,0 +1,246 @@
+/*
+ * Copyright (c) 2023, networkException <git@nwex.de>
+ *
+ * SPDX-License-Identifier: BSD-2-Clause OR MIT
+ */
+
+use std::io;
+
+use tokio::net::UdpSocket;
+
+use crate::{BindableAddr, NamedSocketAddr, SocketAddr, UnixSocketAddr};
+
+#[cfg(unix)]
+use {std::os::unix::io::AsRawFd, tokio::{io::Interest, net::UnixDatagram}, crate::fd};
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::os::unix::net::{SocketAddr as StdUnixSocketAddr, UnixDatagram as StdUnixDatagram};
+#[cfg(target_os = "android")]
+use std::os::android::net::SocketAddrExt;
+#[cfg(target_os = "linux")]
+use std::os::linux::net::SocketAddrExt;
+
+#[derive(Debug)]
+pub enum Datagram {
+    Udp(UdpSocket),
+    #[cfg(unix)]
+    Unix(UnixDatagram),
+}
+
+impl From<UdpSocket> for Datagram {
+    fn from(udp_socket: UdpSocket) -> Self {
+        Datagram::Udp(udp_socket)
+    }
+}
+
+#[cfg(unix)]
+impl From<UnixDatagram> for Datagram {
+    fn from(unix_datagram: UnixDatagram) -> Self {
+        Datagram::Unix(unix_datagram)
+    }
+}
+
+fn mismatched_transport() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "Cannot use a datagram socket with a socket address of a different transport.",
+    )
+}
+
+impl Datagram {
+    // NOTE: Abstract names are only supported on Linux, see SocketAddr::to_bindable.
+    pub async fn bind(bindable_addr: &BindableAddr) -> io::Result<Datagram> {
+        let named_socket_addr = match bindable_addr {
+            BindableAddr::Named(named_socket_addr) => named_socket_addr,
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            BindableAddr::Abstract(name) => {
+                let unix_datagram = StdUnixDatagram::bind_addr(&StdUnixSocketAddr::from_abstract_name(name)?)?;
+                unix_datagram.set_nonblocking(true)?;
+
+                return UnixDatagram::from_std(unix_datagram).map(Datagram::Unix)
+            }
+            #[cfg(not(any(target_os = "linux", target_os = "android")))]
+            BindableAddr::Abstract(_) => return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot bind to an abstract unix socket on this system.",
+            )),
+        };
+
+        match named_socket_addr {
+            NamedSocketAddr::Inet(inet_socket_addr) => UdpSocket::bind(inet_socket_addr).await.map(Datagram::Udp),
+            #[cfg(unix)]
+            NamedSocketAddr::Unix(path) => UnixDatagram::bind(path).map(Datagram::Unix),
+            #[cfg(not(unix))]
+            NamedSocketAddr::Unix(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot bind to a unix socket on a non unix system.",
+            )),
+            #[cfg(windows)]
+            NamedSocketAddr::Pipe(_) => Err(mismatched_transport()),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            NamedSocketAddr::Vsock { .. } => Err(mismatched_transport()),
+        }
+    }
+
+    #[cfg(unix)]
+    pub fn unbound_unix() -> io::Result<Datagram> {
+        UnixDatagram::unbound().map(Datagram::Unix)
+    }
+
+    pub async fn connect(&self, socket_addr: &SocketAddr) -> io::Result<()> {
+        match (self, socket_addr) {
+            (Datagram::Udp(udp_socket), SocketAddr::Inet(inet_socket_addr)) => udp_socket.connect(inet_socket_addr).await,
+            (_, SocketAddr::Unix(UnixSocketAddr::Unnamed)) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot connect to an unnamed unix socket.",
+            )),
+            #[cfg(unix)]
+            (Datagram::Unix(unix_datagram), SocketAddr::Unix(UnixSocketAddr::Pathname(path))) => unix_datagram.connect(path),
+            #[cfg(unix)]
+            (Datagram::Unix(unix_datagram), SocketAddr::Unix(unix_socket_addr)) => fd::connect_unix(unix_datagram.as_raw_fd(), unix_socket_addr),
+            _ => Err(mismatched_transport()),
+        }
+    }
+
+    pub async fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Datagram::Udp(udp_socket) => udp_socket.send(buf).await,
+            #[cfg(unix)]
+            Datagram::Unix(unix_datagram) => unix_datagram.send(buf).await,
+        }
+    }
+
+    pub async fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Datagram::Udp(udp_socket) => udp_socket.recv(buf).await,
+            #[cfg(unix)]
+            Datagram::Unix(unix_datagram) => unix_datagram.recv(buf).await,
+        }
+    }
+
+    pub async fn send_to(&self, buf: &[u8], socket_addr: &SocketAddr) -> io::Result<usize> {
+        match (self, socket_addr) {
+            (Datagram::Udp(udp_socket), SocketAddr::Inet(inet_socket_addr)) => udp_socket.send_to(buf, inet_socket_addr).await,
+            (_, SocketAddr::Unix(UnixSocketAddr::Unnamed)) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot send to an unnamed unix socket.",
+            )),
+            #[cfg(unix)]
+            (Datagram::Unix(unix_datagram), SocketAddr::Unix(UnixSocketAddr::Pathname(path))) => unix_datagram.send_to(buf, path).await,
+            #[cfg(unix)]
+            (Datagram::Unix(unix_datagram), SocketAddr::Unix(unix_socket_addr)) => unix_datagram
+                .async_io(Interest::WRITABLE, || fd::send_to_unix(unix_datagram.as_raw_fd(), buf, unix_socket_addr))
+                .await,
+            _ => Err(mismatched_transport()),
+        }
+    }
+
+    pub async fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        match self {
+            Datagram::Udp(udp_socket) => udp_socket
+                .recv_from(buf)
+                .await
+                .map(|(size, inet_socket_addr)| (size, SocketAddr::Inet(inet_socket_addr))),
+            #[cfg(unix)]
+            Datagram::Unix(unix_datagram) => unix_datagram
+                .recv_from(buf)
+                .await
+                .map(|(size, unix_socket_addr)| (size, SocketAddr::Unix(unix_socket_addr.into()))),
+        }
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        match self {
+            Datagram::Udp(udp_socket) => udp_socket.local_addr().map(SocketAddr::Inet),
+            #[cfg(unix)]
+            Datagram::Unix(unix_datagram) => Ok(SocketAddr::Unix(unix_datagram.local_addr()?.into())),
+        }
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        match self {
+            Datagram::Udp(udp_socket) => udp_socket.peer_addr().map(SocketAddr::Inet),
+            #[cfg(unix)]
+            Datagram::Unix(unix_datagram) => Ok(SocketAddr::Unix(unix_datagram.peer_addr()?.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    #[tokio::test]
+    async fn udp_send_and_recv() {
+        let a = Datagram::bind(&NamedSocketAddr::from((test_util::LOCALHOST, 0)).into()).await.unwrap();
+        let b = Datagram::bind(&NamedSocketAddr::from((test_util::LOCALHOST, 0)).into()).await.unwrap();
+        let b_addr = b.local_addr().unwrap();
+
+        a.send_to(b"ping", &b_addr).await.unwrap();
+
+        let mut buf = [0; 8];
+        let (size, from) = b.recv_from(&mut buf).await.unwrap();
+
+        assert_eq!(&buf[..size], b"ping");
+        assert_eq!(from, a.local_addr().unwrap());
+
+        a.connect(&b_addr).await.unwrap();
+        a.send(b"pong").await.unwrap();
+
+        assert_eq!(b.recv(&mut buf).await.unwrap(), 4);
+        assert_eq!(a.peer_addr().unwrap(), b.local_addr().unwrap());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn unix_send_and_recv() {
+        let path = test_util::temp_path("datagram.sock");
+        let server = Datagram::bind(&NamedSocketAddr::unix(&path).into()).await.unwrap();
+        let client = Datagram::unbound_unix().unwrap();
+
+        client.send_to(b"ping", &SocketAddr::unix(&path)).await.unwrap();
+
+        let mut buf = [0; 8];
+        let (size, from) = server.recv_from(&mut buf).await.unwrap();
+
+        assert_eq!(&buf[..size], b"ping");
+        assert_eq!(from, SocketAddr::Unix(UnixSocketAddr::Unnamed));
+
+        assert_eq!(
+            client.send_to(b"ping", &SocketAddr::Inet((test_util::LOCALHOST, 9).into())).await.unwrap_err().kind(),
+            io::ErrorKind::InvalidInput,
+        );
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn abstract_send_and_recv() {
+        let name = format!("tokio-unix-tcp-{}-datagram", std::process::id()).into_bytes();
+        let abstract_addr = SocketAddr::Unix(UnixSocketAddr::Abstract(name.clone()));
+        let server = Datagram::bind(&BindableAddr::Abstract(name)).await.unwrap();
+        let client = Datagram::unbound_unix().unwrap();
+
+        assert_eq!(server.local_addr().unwrap(), abstract_addr);
+
+        client.send_to(b"ping", &abstract_addr).await.unwrap();
+
+        let mut buf = [0; 8];
+        let (size, from) = server.recv_from(&mut buf).await.unwrap();
+
+        assert_eq!(&buf[..size], b"ping");
+        assert_eq!(from, SocketAddr::Unix(UnixSocketAddr::Unnamed));
+
+        client.connect(&abstract_addr).await.unwrap();
+        client.send(b"pong").await.unwrap();
+
+        assert_eq!(server.recv(&mut buf).await.unwrap(), 4);
+        assert_eq!(client.peer_addr().unwrap(), abstract_addr);
+        assert_eq!(
+            client.send_to(b"ping", &SocketAddr::Unix(UnixSocketAddr::Unnamed)).await.unwrap_err().kind(),
+            io::ErrorKind::Unsupported,
+        );
+    }
+}
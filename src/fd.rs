@@ -0,0 +1,425 @@
+/*
+ * Copyright (c) 2023, networkException <git@nwex.de>
+ *
+ * SPDX-License-Identifier: BSD-2-Clause OR MIT
+ */
+
+use std::io;
+use std::mem;
+use std::net;
+use std::os::unix::io::RawFd;
+use std::time::Duration;
+
+use socket2::{SockAddr, Socket};
+use tokio::io::unix::AsyncFd;
+use tokio::time;
+
+use crate::UnixSocketAddr;
+
+// NOTE: Polling interval while the backlog of a unix listener is full, see connect.
+const UNIX_CONNECT_RETRY_INTERVAL: Duration = Duration::from_millis(10);
+
+pub(crate) fn socket_domain(fd: RawFd) -> io::Result<libc::c_int> {
+    let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+    let mut length = mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+
+    if unsafe { libc::getsockname(fd, (&mut storage as *mut libc::sockaddr_storage).cast(), &mut length) } < 0 {
+        return Err(io::Error::last_os_error())
+    }
+
+    Ok(storage.ss_family as libc::c_int)
+}
+
+fn socket_option(fd: RawFd, name: libc::c_int) -> io::Result<libc::c_int> {
+    let mut value: libc::c_int = 0;
+    let mut length = mem::size_of::<libc::c_int>() as libc::socklen_t;
+
+    if unsafe { libc::getsockopt(fd, libc::SOL_SOCKET, name, (&mut value as *mut libc::c_int).cast(), &mut length) } < 0 {
+        return Err(io::Error::last_os_error())
+    }
+
+    Ok(value)
+}
+
+// Checks that an inherited socket (see NamedSocketAddr::inherited_fd) is a stream socket
+// that is listening or not, matching whether it is adopted by a Listener or a Stream.
+pub(crate) fn check_inherited(fd: RawFd, listening: bool) -> io::Result<()> {
+    let is_stream = socket_option(fd, libc::SO_TYPE)? == libc::SOCK_STREAM;
+    let is_listening = socket_option(fd, libc::SO_ACCEPTCONN)? != 0;
+
+    if !is_stream || is_listening != listening {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Cannot adopt inherited socket {}, it is not a {} stream socket.",
+                fd,
+                if listening { "listening" } else { "connected" },
+            ),
+        ))
+    }
+
+    Ok(())
+}
+
+// Connects a nonblocking socket without blocking the runtime. Pending connections (EINPROGRESS)
+// complete once the socket becomes writable.
+// NOTE: Unix sockets fail with EAGAIN instead while the backlog of the listener is full. The
+//       socket doesn't become writable once there is room again, so connecting is retried.
+pub(crate) async fn connect(socket: &AsyncFd<Socket>, sock_addr: &SockAddr) -> io::Result<()> {
+    loop {
+        let error = match socket.get_ref().connect(sock_addr) {
+            Ok(()) => return Ok(()),
+            Err(error) => error,
+        };
+
+        match error.raw_os_error() {
+            Some(libc::EINPROGRESS) => {
+                let _guard = socket.writable().await?;
+
+                return match socket.get_ref().take_error()? {
+                    Some(error) => Err(error),
+                    None => Ok(()),
+                }
+            }
+            Some(libc::EAGAIN) => time::sleep(UNIX_CONNECT_RETRY_INTERVAL).await,
+            _ => return Err(error),
+        }
+    }
+}
+
+// Returns a close on exec duplicate of fd, leaving the original owned by the caller.
+// NOTE: tokio's unix datagram sockets only take paths, connect_unix and send_to_unix
+//       also reach abstract names.
+pub(crate) fn connect_unix(fd: RawFd, unix_socket_addr: &UnixSocketAddr) -> io::Result<()> {
+    let (sockaddr, length) = unix_socket_addr.to_sockaddr_bytes()?;
+
+    if unsafe { libc::connect(fd, (&sockaddr as *const libc::sockaddr_un).cast(), length) } < 0 {
+        return Err(io::Error::last_os_error())
+    }
+
+    Ok(())
+}
+
+pub(crate) fn send_to_unix(fd: RawFd, buf: &[u8], unix_socket_addr: &UnixSocketAddr) -> io::Result<usize> {
+    let (sockaddr, length) = unix_socket_addr.to_sockaddr_bytes()?;
+    let sent = unsafe { libc::sendto(fd, buf.as_ptr().cast(), buf.len(), 0, (&sockaddr as *const libc::sockaddr_un).cast(), length) };
+
+    if sent < 0 {
+        return Err(io::Error::last_os_error())
+    }
+
+    Ok(sent as usize)
+}
+
+pub(crate) fn duplicate(fd: RawFd) -> io::Result<RawFd> {
+    let duplicate = unsafe { libc::fcntl(fd, libc::F_DUPFD_CLOEXEC, 0) };
+
+    if duplicate < 0 {
+        return Err(io::Error::last_os_error())
+    }
+
+    Ok(duplicate)
+}
+
+// Returns the device and inode of the socket, which are only shared by duplicates of
+// the same file descriptor.
+pub(crate) fn socket_identity(fd: RawFd) -> io::Result<(libc::dev_t, libc::ino_t)> {
+    let mut stat: libc::stat = unsafe { mem::zeroed() };
+
+    if unsafe { libc::fstat(fd, &mut stat) } < 0 {
+        return Err(io::Error::last_os_error())
+    }
+
+    Ok((stat.st_dev, stat.st_ino))
+}
+
+pub(crate) fn set_cloexec(fd: RawFd, cloexec: bool) -> io::Result<()> {
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFD) };
+
+    if flags < 0 {
+        return Err(io::Error::last_os_error())
+    }
+
+    let flags = if cloexec { flags | libc::FD_CLOEXEC } else { flags & !libc::FD_CLOEXEC };
+
+    if unsafe { libc::fcntl(fd, libc::F_SETFD, flags) } < 0 {
+        return Err(io::Error::last_os_error())
+    }
+
+    Ok(())
+}
+
+pub(crate) fn set_nonblocking(fd: RawFd, nonblocking: bool) -> io::Result<()> {
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+
+    if flags < 0 {
+        return Err(io::Error::last_os_error())
+    }
+
+    let flags = if nonblocking { flags | libc::O_NONBLOCK } else { flags & !libc::O_NONBLOCK };
+
+    if unsafe { libc::fcntl(fd, libc::F_SETFL, flags) } < 0 {
+        return Err(io::Error::last_os_error())
+    }
+
+    Ok(())
+}
+
+// NOTE: IPv6 sockets use IPV6_TCLASS instead of IP_TOS, except for IPv4 traffic on
+//       dual stack sockets.
+fn tos_option(ipv6: bool) -> (libc::c_int, libc::c_int) {
+    if ipv6 {
+        (libc::IPPROTO_IPV6, libc::IPV6_TCLASS)
+    } else {
+        (libc::IPPROTO_IP, libc::IP_TOS)
+    }
+}
+
+pub(crate) fn set_tos(fd: RawFd, ipv6: bool, tos: u32) -> io::Result<()> {
+    let (level, name) = tos_option(ipv6);
+    let tos = tos as libc::c_int;
+    let length = mem::size_of::<libc::c_int>() as libc::socklen_t;
+
+    if unsafe { libc::setsockopt(fd, level, name, (&tos as *const libc::c_int).cast(), length) } < 0 {
+        return Err(io::Error::last_os_error())
+    }
+
+    Ok(())
+}
+
+pub(crate) fn tos(fd: RawFd, ipv6: bool) -> io::Result<u32> {
+    let (level, name) = tos_option(ipv6);
+    let mut tos: libc::c_int = 0;
+    let mut length = mem::size_of::<libc::c_int>() as libc::socklen_t;
+
+    if unsafe { libc::getsockopt(fd, level, name, (&mut tos as *mut libc::c_int).cast(), &mut length) } < 0 {
+        return Err(io::Error::last_os_error())
+    }
+
+    Ok(tos as u32)
+}
+
+// Returns the bytes in the send queue the peer didn't acknowledge yet (SIOCOUTQ) and the
+// bytes in the receive queue that weren't read yet (SIOCINQ).
+#[cfg(target_os = "linux")]
+pub(crate) fn queued_bytes(fd: RawFd) -> io::Result<(usize, usize)> {
+    let mut unsent: libc::c_int = 0;
+    let mut unread: libc::c_int = 0;
+
+    // NOTE: SIOCOUTQ and SIOCINQ share their values with TIOCOUTQ and FIONREAD.
+    if unsafe { libc::ioctl(fd, libc::TIOCOUTQ, &mut unsent) } < 0 {
+        return Err(io::Error::last_os_error())
+    }
+
+    if unsafe { libc::ioctl(fd, libc::FIONREAD, &mut unread) } < 0 {
+        return Err(io::Error::last_os_error())
+    }
+
+    Ok((unsent as usize, unread as usize))
+}
+
+// NOTE: Setting SO_MARK requires CAP_NET_ADMIN, the resulting EPERM is reported as
+//       io::ErrorKind::PermissionDenied.
+#[cfg(target_os = "linux")]
+pub(crate) fn set_mark(fd: RawFd, mark: u32) -> io::Result<()> {
+    let length = mem::size_of::<u32>() as libc::socklen_t;
+
+    if unsafe { libc::setsockopt(fd, libc::SOL_SOCKET, libc::SO_MARK, (&mark as *const u32).cast(), length) } < 0 {
+        return Err(io::Error::last_os_error())
+    }
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn mark(fd: RawFd) -> io::Result<u32> {
+    let mut mark: u32 = 0;
+    let mut length = mem::size_of::<u32>() as libc::socklen_t;
+
+    if unsafe { libc::getsockopt(fd, libc::SOL_SOCKET, libc::SO_MARK, (&mut mark as *mut u32).cast(), &mut length) } < 0 {
+        return Err(io::Error::last_os_error())
+    }
+
+    Ok(mark)
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn set_pass_cred(fd: RawFd, pass_cred: bool) -> io::Result<()> {
+    let pass_cred = pass_cred as libc::c_int;
+    let length = mem::size_of::<libc::c_int>() as libc::socklen_t;
+
+    if unsafe { libc::setsockopt(fd, libc::SOL_SOCKET, libc::SO_PASSCRED, (&pass_cred as *const libc::c_int).cast(), length) } < 0 {
+        return Err(io::Error::last_os_error())
+    }
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn pass_cred(fd: RawFd) -> io::Result<bool> {
+    let mut pass_cred: libc::c_int = 0;
+    let mut length = mem::size_of::<libc::c_int>() as libc::socklen_t;
+
+    if unsafe { libc::getsockopt(fd, libc::SOL_SOCKET, libc::SO_PASSCRED, (&mut pass_cred as *mut libc::c_int).cast(), &mut length) } < 0 {
+        return Err(io::Error::last_os_error())
+    }
+
+    Ok(pass_cred != 0)
+}
+
+// NOTE: Since Linux 5.7 binding to a device only requires CAP_NET_RAW if the socket
+//       was already bound to a different one, older kernels always require it.
+#[cfg(target_os = "linux")]
+pub(crate) fn bind_to_device(fd: RawFd, interface: &str) -> io::Result<()> {
+    let length = interface.len() as libc::socklen_t;
+
+    if unsafe { libc::setsockopt(fd, libc::SOL_SOCKET, libc::SO_BINDTODEVICE, interface.as_ptr().cast(), length) } < 0 {
+        let error = io::Error::last_os_error();
+
+        if error.kind() == io::ErrorKind::PermissionDenied {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "Cannot bind to a device without CAP_NET_RAW.",
+            ))
+        }
+
+        return Err(error)
+    }
+
+    Ok(())
+}
+
+// NOTE: Only the fields every kernel since 2.6 fills in. The kernel copies at most the
+//       requested length, so newer kernels with a longer struct tcp_info are fine.
+#[cfg(target_os = "linux")]
+#[repr(C)]
+#[derive(Default)]
+pub(crate) struct RawTcpInfo {
+    pub(crate) state: u8,
+    pub(crate) ca_state: u8,
+    pub(crate) retransmits: u8,
+    pub(crate) probes: u8,
+    pub(crate) backoff: u8,
+    pub(crate) options: u8,
+    pub(crate) wscale: u8,
+    pub(crate) flags: u8,
+    pub(crate) rto: u32,
+    pub(crate) ato: u32,
+    pub(crate) snd_mss: u32,
+    pub(crate) rcv_mss: u32,
+    pub(crate) unacked: u32,
+    pub(crate) sacked: u32,
+    pub(crate) lost: u32,
+    pub(crate) retrans: u32,
+    pub(crate) fackets: u32,
+    pub(crate) last_data_sent: u32,
+    pub(crate) last_ack_sent: u32,
+    pub(crate) last_data_recv: u32,
+    pub(crate) last_ack_recv: u32,
+    pub(crate) pmtu: u32,
+    pub(crate) rcv_ssthresh: u32,
+    pub(crate) rtt: u32,
+    pub(crate) rttvar: u32,
+    pub(crate) snd_ssthresh: u32,
+    pub(crate) snd_cwnd: u32,
+    pub(crate) advmss: u32,
+    pub(crate) reordering: u32,
+    pub(crate) rcv_rtt: u32,
+    pub(crate) rcv_space: u32,
+    pub(crate) total_retrans: u32,
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn tcp_info(fd: RawFd) -> io::Result<RawTcpInfo> {
+    let mut info = RawTcpInfo::default();
+    let mut length = mem::size_of::<RawTcpInfo>() as libc::socklen_t;
+
+    if unsafe { libc::getsockopt(fd, libc::IPPROTO_TCP, libc::TCP_INFO, (&mut info as *mut RawTcpInfo).cast(), &mut length) } < 0 {
+        return Err(io::Error::last_os_error())
+    }
+
+    if (length as usize) < mem::size_of::<RawTcpInfo>() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Cannot parse TCP_INFO, the kernel returned less data than expected.",
+        ))
+    }
+
+    Ok(info)
+}
+
+// NOTE: Only connections redirected by netfilter (e.g. iptables REDIRECT or TPROXY) have an
+//       original destination, getsockopt fails with ENOENT for any other connection.
+#[cfg(target_os = "linux")]
+pub(crate) fn original_dst(fd: RawFd, ipv6: bool) -> io::Result<net::SocketAddr> {
+    let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+    let mut length = mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+
+    let (level, name) = if ipv6 {
+        (libc::SOL_IPV6, libc::IP6T_SO_ORIGINAL_DST)
+    } else {
+        (libc::SOL_IP, libc::SO_ORIGINAL_DST)
+    };
+
+    if unsafe { libc::getsockopt(fd, level, name, (&mut storage as *mut libc::sockaddr_storage).cast(), &mut length) } < 0 {
+        let error = io::Error::last_os_error();
+
+        if error.raw_os_error() == Some(libc::ENOENT) {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot get the original destination of a connection that wasn't redirected.",
+            ))
+        }
+
+        return Err(error)
+    }
+
+    inet_socket_addr(&storage).ok_or_else(|| io::Error::new(
+        io::ErrorKind::InvalidData,
+        "Cannot parse the original destination address.",
+    ))
+}
+
+#[cfg(target_os = "linux")]
+fn inet_socket_addr(storage: &libc::sockaddr_storage) -> Option<net::SocketAddr> {
+    match storage.ss_family as libc::c_int {
+        libc::AF_INET => {
+            let sockaddr = unsafe { &*(storage as *const libc::sockaddr_storage).cast::<libc::sockaddr_in>() };
+            let ip = net::Ipv4Addr::from(u32::from_be(sockaddr.sin_addr.s_addr));
+
+            Some(net::SocketAddr::from((ip, u16::from_be(sockaddr.sin_port))))
+        }
+        libc::AF_INET6 => {
+            let sockaddr = unsafe { &*(storage as *const libc::sockaddr_storage).cast::<libc::sockaddr_in6>() };
+            let ip = net::Ipv6Addr::from(sockaddr.sin6_addr.s6_addr);
+
+            Some(net::SocketAddr::V6(net::SocketAddrV6::new(
+                ip,
+                u16::from_be(sockaddr.sin6_port),
+                sockaddr.sin6_flowinfo,
+                sockaddr.sin6_scope_id,
+            )))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_inet_socket_addrs() {
+        let inet_socket_addrs: [net::SocketAddr; 2] = ["10.0.0.1:8080".parse().unwrap(), "[fe80::1%2]:443".parse().unwrap()];
+
+        for expected in inet_socket_addrs {
+            let storage = SockAddr::from(expected).as_storage();
+
+            assert_eq!(inet_socket_addr(&storage), Some(expected));
+        }
+
+        let storage = SockAddr::unix("/run/app.sock").unwrap().as_storage();
+
+        assert_eq!(inet_socket_addr(&storage), None);
+    }
+}
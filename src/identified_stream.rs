@@ -0,0 +1,129 @@
+/*
+ * Copyright (c) 2023, networkException <git@nwex.de>
+ *
+ * SPDX-License-Identifier: BSD-2-Clause OR MIT
+ */
+
+use std::io;
+use std::pin::Pin;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::Stream;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+// NOTE: Stream is an enum of the transport types, so the id is stored in this wrapper
+//       instead. Ids are assigned on the first call to id, in the order streams are first
+//       asked for theirs, and are never reused within the process.
+#[derive(Debug)]
+pub struct IdentifiedStream {
+    inner: Stream,
+    id: OnceLock<u64>,
+}
+
+impl IdentifiedStream {
+    pub fn new(stream: Stream) -> IdentifiedStream {
+        IdentifiedStream {
+            inner: stream,
+            id: OnceLock::new(),
+        }
+    }
+
+    pub fn id(&self) -> u64 {
+        *self.id.get_or_init(|| NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+
+    pub fn get_ref(&self) -> &Stream {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut Stream {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> Stream {
+        self.inner
+    }
+}
+
+impl From<Stream> for IdentifiedStream {
+    fn from(stream: Stream) -> IdentifiedStream {
+        IdentifiedStream::new(stream)
+    }
+}
+
+impl AsyncRead for IdentifiedStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut Pin::into_inner(self).inner).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for IdentifiedStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut Pin::into_inner(self).inner).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut Pin::into_inner(self).inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut Pin::into_inner(self).inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut Pin::into_inner(self).inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+    use crate::test_util;
+
+    #[tokio::test]
+    async fn assigns_stable_ids() {
+        let (a, b) = test_util::tcp_pair().await;
+        let mut a = IdentifiedStream::new(a);
+        let mut b = IdentifiedStream::from(b);
+
+        // NOTE: Ids are assigned in the order they are first asked for.
+        let b_id = b.id();
+        let a_id = a.id();
+
+        assert!(a_id > b_id);
+        assert_eq!(a.id(), a_id);
+        assert_eq!(b.id(), b_id);
+
+        a.write_all(b"ping").await.unwrap();
+
+        let mut buf = [0; 4];
+        b.read_exact(&mut buf).await.unwrap();
+
+        assert_eq!(&buf, b"ping");
+        assert_eq!(b.id(), b_id);
+        assert_eq!(b.get_ref().transport(), crate::Transport::Tcp);
+    }
+}
@@ -0,0 +1,170 @@
+/*
+ * Copyright (c) 2023, networkException <git@nwex.de>
+ *
+ * SPDX-License-Identifier: BSD-2-Clause OR MIT
+ */
+
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::time::{self, Instant, Sleep};
+
+// NOTE: Reads and writes share one deadline, which is pushed back whenever either of
+//       them makes progress. Once it elapsed, every pending read or write fails with
+//       io::ErrorKind::TimedOut until one of them makes progress again.
+#[derive(Debug)]
+pub struct IdleTimeout<S> {
+    inner: S,
+    timeout: Duration,
+    deadline: Pin<Box<Sleep>>,
+}
+
+impl<S> IdleTimeout<S> {
+    pub fn new(inner: S, timeout: Duration) -> IdleTimeout<S> {
+        IdleTimeout {
+            inner,
+            timeout,
+            deadline: Box::pin(time::sleep(timeout)),
+        }
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    fn reset(&mut self) {
+        self.deadline.as_mut().reset(Instant::now() + self.timeout);
+    }
+
+    // Called with the result of polling the inner stream, re-arms the deadline on
+    // progress and polls it otherwise so the task is woken up once it elapses.
+    fn poll_deadline<T>(&mut self, cx: &mut Context<'_>, poll: Poll<io::Result<T>>, progress: bool) -> Poll<io::Result<T>> {
+        match poll {
+            Poll::Ready(Ok(value)) => {
+                if progress {
+                    self.reset();
+                }
+
+                Poll::Ready(Ok(value))
+            }
+            Poll::Ready(Err(error)) => Poll::Ready(Err(error)),
+            Poll::Pending => match self.deadline.as_mut().poll(cx) {
+                Poll::Ready(()) => Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "Connection was idle for too long.",
+                ))),
+                Poll::Pending => Poll::Pending,
+            },
+        }
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for IdleTimeout<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = Pin::into_inner(self);
+        let filled = buf.filled().len();
+
+        let poll = Pin::new(&mut this.inner).poll_read(cx, buf);
+        let progress = buf.filled().len() > filled;
+
+        this.poll_deadline(cx, poll, progress)
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for IdleTimeout<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = Pin::into_inner(self);
+        let poll = Pin::new(&mut this.inner).poll_write(cx, buf);
+        let progress = matches!(poll, Poll::Ready(Ok(written)) if written > 0);
+
+        this.poll_deadline(cx, poll, progress)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let this = Pin::into_inner(self);
+        let poll = Pin::new(&mut this.inner).poll_write_vectored(cx, bufs);
+        let progress = matches!(poll, Poll::Ready(Ok(written)) if written > 0);
+
+        this.poll_deadline(cx, poll, progress)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = Pin::into_inner(self);
+        let poll = Pin::new(&mut this.inner).poll_flush(cx);
+
+        this.poll_deadline(cx, poll, false)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut Pin::into_inner(self).inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+    use crate::test_util;
+
+    #[tokio::test]
+    async fn times_out_when_idle() {
+        let (stream, mut other) = test_util::tcp_pair().await;
+        let mut stream = stream.with_idle_timeout(Duration::from_millis(50));
+
+        assert_eq!(stream.timeout(), Duration::from_millis(50));
+
+        // NOTE: Every read making progress pushes the deadline back.
+        for _ in 0..3 {
+            time::sleep(Duration::from_millis(30)).await;
+            other.write_all(b"x").await.unwrap();
+            stream.read_exact(&mut [0; 1]).await.unwrap();
+        }
+
+        let error = stream.read(&mut [0; 1]).await.unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+
+        // NOTE: Progress in either direction re-arms the timeout.
+        stream.write_all(b"y").await.unwrap();
+        other.read_exact(&mut [0; 1]).await.unwrap();
+        other.write_all(b"z").await.unwrap();
+
+        let mut buf = [0; 1];
+
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"z");
+    }
+}
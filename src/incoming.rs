@@ -0,0 +1,242 @@
+/*
+ * Copyright (c) 2023, networkException <git@nwex.de>
+ *
+ * SPDX-License-Identifier: BSD-2-Clause OR MIT
+ */
+
+use std::error;
+use std::fmt::{self, Display, Formatter};
+use std::future::{self, Future};
+use std::io;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use std::time::Duration;
+
+use tokio::time::{self, Sleep};
+
+use crate::{Listener, SocketAddr, Stream};
+
+// NOTE: Used unless configured otherwise, long enough to not spin while other tasks or
+//       processes release file descriptors.
+pub(crate) const DEFAULT_ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+#[cfg(windows)]
+const WSAEMFILE: i32 = 10024;
+#[cfg(windows)]
+const WSAENOBUFS: i32 = 10055;
+
+// NOTE: PerConnection errors only affect the connection being accepted, the listener
+//       keeps working. After a Fatal error the listener is unusable.
+#[derive(Debug)]
+pub enum AcceptError {
+    PerConnection(io::Error),
+    Fatal(io::Error),
+}
+
+// Yields accepted connections until accepting fails fatally, which is yielded as the
+// last item. Named pipe listeners can't be polled and always fail fatally.
+#[derive(Debug)]
+pub struct IncomingClassified<'a> {
+    listener: &'a Listener,
+    backoff: Option<Duration>,
+    sleep: Option<Pin<Box<Sleep>>>,
+    done: bool,
+}
+
+impl AcceptError {
+    pub fn is_fatal(&self) -> bool {
+        matches!(self, AcceptError::Fatal(_))
+    }
+
+    pub fn into_inner(self) -> io::Error {
+        match self {
+            AcceptError::PerConnection(error) | AcceptError::Fatal(error) => error,
+        }
+    }
+
+    pub(crate) fn classify(error: io::Error) -> AcceptError {
+        let per_connection = is_resource_exhaustion(&error) || is_pending_connection_error(&error) || matches!(
+            error.kind(),
+            io::ErrorKind::ConnectionAborted
+                | io::ErrorKind::ConnectionRefused
+                | io::ErrorKind::ConnectionReset
+                | io::ErrorKind::Interrupted
+                | io::ErrorKind::TimedOut
+        );
+
+        if per_connection {
+            AcceptError::PerConnection(error)
+        } else {
+            AcceptError::Fatal(error)
+        }
+    }
+}
+
+impl<'a> IncomingClassified<'a> {
+    pub(crate) fn new(listener: &'a Listener) -> IncomingClassified<'a> {
+        IncomingClassified {
+            listener,
+            backoff: Some(DEFAULT_ACCEPT_BACKOFF),
+            sleep: None,
+            done: false,
+        }
+    }
+
+    // Waits for the given duration (100ms by default) before accepting again after running
+    // out of file descriptors or memory, which would otherwise fail again right away.
+    pub fn with_backoff(mut self, backoff: Duration) -> IncomingClassified<'a> {
+        self.backoff = Some(backoff);
+        self
+    }
+
+    // Accepts again right away after running out of resources, leaving backing off to the caller.
+    pub fn without_backoff(mut self) -> IncomingClassified<'a> {
+        self.backoff = None;
+        self
+    }
+
+    pub async fn next(&mut self) -> Option<Result<(Stream, SocketAddr), AcceptError>> {
+        future::poll_fn(|cx| self.poll_next(cx)).await
+    }
+
+    pub fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<(Stream, SocketAddr), AcceptError>>> {
+        if self.done {
+            return Poll::Ready(None)
+        }
+
+        if let Some(sleep) = &mut self.sleep {
+            ready!(sleep.as_mut().poll(cx));
+            self.sleep = None;
+        }
+
+        let error = match ready!(self.listener.poll_accept(cx)) {
+            Ok(accepted) => return Poll::Ready(Some(Ok(accepted))),
+            Err(error) => error,
+        };
+
+        if let (Some(backoff), true) = (self.backoff, is_resource_exhaustion(&error)) {
+            self.sleep = Some(Box::pin(time::sleep(backoff)));
+        }
+
+        let error = AcceptError::classify(error);
+
+        self.done = error.is_fatal();
+
+        Poll::Ready(Some(Err(error)))
+    }
+}
+
+impl futures_core::Stream for IncomingClassified<'_> {
+    type Item = Result<(Stream, SocketAddr), AcceptError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::into_inner(self).poll_next(cx)
+    }
+}
+
+pub(crate) fn is_resource_exhaustion(error: &io::Error) -> bool {
+    #[cfg(unix)]
+    return matches!(error.raw_os_error(), Some(libc::EMFILE | libc::ENFILE | libc::ENOBUFS | libc::ENOMEM));
+    #[cfg(windows)]
+    return matches!(error.raw_os_error(), Some(WSAEMFILE | WSAENOBUFS));
+    #[cfg(not(any(unix, windows)))]
+    return error.kind() == io::ErrorKind::OutOfMemory;
+}
+
+// NOTE: On Linux, accept passes on network errors already pending on the new connection,
+//       see accept(2). They should be treated like ECONNABORTED and accepting retried.
+#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+fn is_pending_connection_error(error: &io::Error) -> bool {
+    #[cfg(target_os = "linux")]
+    return matches!(
+        error.raw_os_error(),
+        Some(
+            libc::ENETDOWN
+                | libc::EPROTO
+                | libc::ENOPROTOOPT
+                | libc::EHOSTDOWN
+                | libc::ENONET
+                | libc::EHOSTUNREACH
+                | libc::EOPNOTSUPP
+                | libc::ENETUNREACH
+        )
+    );
+    #[cfg(not(target_os = "linux"))]
+    return false;
+}
+
+impl Display for AcceptError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            AcceptError::PerConnection(error) => write!(formatter, "cannot accept connection: {}", error),
+            AcceptError::Fatal(error) => write!(formatter, "cannot accept on listener: {}", error),
+        }
+    }
+}
+
+impl error::Error for AcceptError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            AcceptError::PerConnection(error) | AcceptError::Fatal(error) => Some(error),
+        }
+    }
+}
+
+impl From<AcceptError> for io::Error {
+    fn from(accept_error: AcceptError) -> io::Error {
+        accept_error.into_inner()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    #[tokio::test]
+    async fn yields_connections() {
+        let (listener, _) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+        let named_socket_addr = listener.local_addr().unwrap().to_named_socket_addr().unwrap();
+        let mut incoming = listener.incoming_classified().with_backoff(Duration::from_millis(1));
+
+        for _ in 0..2 {
+            let (client, accepted) = tokio::join!(Stream::connect(&named_socket_addr), incoming.next());
+            let (_, socket_addr) = accepted.unwrap().unwrap();
+
+            assert_eq!(socket_addr, client.unwrap().local_addr().unwrap());
+        }
+    }
+
+    #[test]
+    fn classify() {
+        assert!(!AcceptError::classify(io::Error::from(io::ErrorKind::ConnectionAborted)).is_fatal());
+        assert!(!AcceptError::classify(io::Error::from(io::ErrorKind::Interrupted)).is_fatal());
+        assert!(AcceptError::classify(io::Error::from(io::ErrorKind::InvalidInput)).is_fatal());
+
+        #[cfg(unix)]
+        for errno in [libc::EMFILE, libc::ENFILE, libc::ENOBUFS, libc::ENOMEM] {
+            assert!(is_resource_exhaustion(&io::Error::from_raw_os_error(errno)));
+            assert!(!AcceptError::classify(io::Error::from_raw_os_error(errno)).is_fatal());
+        }
+
+        #[cfg(target_os = "linux")]
+        assert!(!AcceptError::classify(io::Error::from_raw_os_error(libc::EHOSTUNREACH)).is_fatal());
+
+        #[cfg(unix)]
+        assert!(AcceptError::classify(io::Error::from_raw_os_error(libc::EBADF)).is_fatal());
+
+        let error = AcceptError::classify(io::Error::from(io::ErrorKind::ConnectionReset));
+
+        assert!(error.to_string().starts_with("cannot accept connection: "));
+        assert_eq!(error.into_inner().kind(), io::ErrorKind::ConnectionReset);
+    }
+
+    #[tokio::test]
+    async fn default_backoff() {
+        let (listener, _) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+
+        assert_eq!(listener.incoming_classified().backoff, Some(DEFAULT_ACCEPT_BACKOFF));
+        assert_eq!(listener.incoming_classified().with_backoff(Duration::from_secs(1)).backoff, Some(Duration::from_secs(1)));
+        assert_eq!(listener.incoming_classified().without_backoff().backoff, None);
+    }
+}
@@ -4,10 +4,108 @@
  * SPDX-License-Identifier: BSD-2-Clause OR MIT
  */
 
+#[cfg(all(feature = "net", unix))]
+mod ancillary;
+#[cfg(feature = "net")]
+mod buffered_stream;
+#[cfg(feature = "codec")]
+mod codec;
+#[cfg(feature = "net")]
+mod connect;
+#[cfg(feature = "net")]
+mod connection_source;
+#[cfg(feature = "net")]
+mod counting_stream;
+#[cfg(feature = "net")]
+mod datagram;
+#[cfg(all(feature = "net", unix))]
+mod fd;
+#[cfg(feature = "net")]
+mod identified_stream;
+#[cfg(feature = "net")]
+mod idle_timeout;
+#[cfg(feature = "net")]
+mod incoming;
+#[cfg(feature = "net")]
 mod listener;
-mod socket_addr;
+#[cfg(feature = "net")]
+mod memory_stream;
+#[cfg(feature = "net")]
+mod multi_listener;
+#[cfg(all(feature = "net", windows))]
+mod named_pipe;
+#[cfg(all(feature = "net", any(unix, windows)))]
+mod pool;
+#[cfg(feature = "proxy-protocol")]
+mod proxy_protocol;
+#[cfg(feature = "net")]
+mod reconnecting_stream;
+#[cfg(feature = "net")]
+mod reset_as_eof;
+#[cfg(all(feature = "net", unix))]
+mod seqpacket;
+#[cfg(feature = "net")]
+mod shared_listener;
+pub mod socket_addr;
+#[cfg(all(feature = "net", any(unix, windows)))]
+mod socket_type;
+#[cfg(feature = "socks")]
+mod socks;
+#[cfg(all(feature = "net", target_os = "linux"))]
+mod splice;
+#[cfg(feature = "net")]
 mod stream;
+#[cfg(all(test, feature = "net"))]
+mod test_util;
+#[cfg(feature = "tls")]
+mod tls;
+mod transport;
+#[cfg(all(feature = "vsock", target_os = "linux"))]
+mod vsock;
 
-pub use listener::Listener;
-pub use socket_addr::{SocketAddr, NamedSocketAddr, UnixSocketAddr};
-pub use stream::Stream;
+#[cfg(feature = "net")]
+pub use buffered_stream::BufferedStream;
+#[cfg(feature = "net")]
+pub use connect::Connect;
+#[cfg(feature = "net")]
+pub use connection_source::{AcceptFuture, BoxedConnectionSource, ConnectionSource};
+#[cfg(feature = "net")]
+pub use counting_stream::CountingStream;
+#[cfg(feature = "net")]
+pub use datagram::Datagram;
+#[cfg(feature = "net")]
+pub use identified_stream::IdentifiedStream;
+#[cfg(feature = "net")]
+pub use idle_timeout::IdleTimeout;
+#[cfg(feature = "net")]
+pub use incoming::{AcceptError, IncomingClassified};
+#[cfg(feature = "net")]
+pub use listener::{BindOutcome, Listener, StdListener};
+#[cfg(feature = "net")]
+pub use memory_stream::MemoryStream;
+#[cfg(feature = "net")]
+pub use multi_listener::MultiListener;
+#[cfg(all(feature = "net", windows))]
+pub use named_pipe::NamedPipeListener;
+#[cfg(all(feature = "net", any(unix, windows)))]
+pub use pool::{Pool, PooledStream};
+#[cfg(feature = "net")]
+pub use reconnecting_stream::ReconnectingStream;
+#[cfg(feature = "net")]
+pub use reset_as_eof::ResetAsEof;
+#[cfg(all(feature = "net", unix))]
+pub use seqpacket::{SeqPacket, SeqPacketListener};
+#[cfg(feature = "net")]
+pub use shared_listener::SharedListener;
+pub use socket_addr::{SocketAddr, NamedSocketAddr, BindableAddr, UnixSocketAddr, ParseSocketAddrError};
+#[cfg(all(feature = "net", any(unix, windows)))]
+pub use socket_type::SocketType;
+#[cfg(feature = "net")]
+pub use stream::{QueuedBytes, Stream, StdStream};
+#[cfg(all(feature = "net", target_os = "linux"))]
+pub use stream::{Credentials, TcpInfo};
+#[cfg(feature = "tls")]
+pub use tls::TlsStream;
+pub use transport::Transport;
+#[cfg(all(feature = "vsock", target_os = "linux"))]
+pub use vsock::VsockListener;
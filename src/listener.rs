@@ -4,26 +4,73 @@
  * SPDX-License-Identifier: BSD-2-Clause OR MIT
  */
 
+use std::future::{self, Future};
 use std::io;
+use std::mem;
+use std::net::{self, IpAddr};
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
 
 #[cfg(unix)]
 use std::{
-    os::unix::prelude::PermissionsExt,
-    fs::{self, Permissions}
+    os::unix::{self, fs::{chown, DirBuilderExt, FileTypeExt}, io::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, OwnedFd, RawFd}, prelude::PermissionsExt},
+    fs::{self, Permissions},
+    path::Path,
 };
 
+#[cfg(any(unix, windows))]
+use socket2::{Domain, Protocol, SockAddr, Socket, Type};
 use tokio::net::TcpListener;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tokio::time::{self, Instant};
 
-use crate::{SocketAddr, Stream, NamedSocketAddr};
+#[cfg(feature = "tracing")]
+use tracing::Instrument;
+
+use crate::{AcceptError, BindableAddr, ConnectionSource, IncomingClassified, SocketAddr, SharedListener, Stream, NamedSocketAddr, Transport};
+
+use crate::incoming;
+#[cfg(unix)]
+use crate::{fd, UnixSocketAddr};
 
 #[cfg(unix)]
-use tokio::net::UnixListener;
+use tokio::net::{UnixListener, UnixStream};
+
+#[cfg(windows)]
+use crate::NamedPipeListener;
 
+#[cfg(all(feature = "vsock", target_os = "linux"))]
+use crate::VsockListener;
+
+// NOTE: Non exhaustive as the variants depend on the platform and enabled features.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Listener {
     Tcp(TcpListener),
+    // NOTE: The socket file of listeners adopted from an inherited socket belongs to whoever
+    //       passed them on, so close doesn't remove it.
+    #[cfg(unix)]
+    Unix { listener: UnixListener, inherited: bool },
+    #[cfg(windows)]
+    Pipe(NamedPipeListener),
+    #[cfg(all(feature = "vsock", target_os = "linux"))]
+    Vsock(VsockListener),
+}
+
+#[derive(Debug)]
+pub struct BindOutcome {
+    pub listener: Listener,
+    pub removed_stale: bool,
+}
+
+#[derive(Debug)]
+pub enum StdListener {
+    Tcp(net::TcpListener),
     #[cfg(unix)]
-    Unix(UnixListener),
+    Unix(unix::net::UnixListener),
 }
 
 impl From<TcpListener> for Listener {
@@ -35,34 +82,322 @@ impl From<TcpListener> for Listener {
 #[cfg(unix)]
 impl From<UnixListener> for Listener {
     fn from(listener: UnixListener) -> Listener {
-        Listener::Unix(listener)
+        Listener::Unix { listener, inherited: false }
+    }
+}
+
+#[cfg(all(feature = "vsock", target_os = "linux"))]
+impl From<VsockListener> for Listener {
+    fn from(listener: VsockListener) -> Listener {
+        Listener::Vsock(listener)
+    }
+}
+
+#[cfg(windows)]
+impl From<NamedPipeListener> for Listener {
+    fn from(listener: NamedPipeListener) -> Listener {
+        Listener::Pipe(listener)
     }
 }
 
 impl Listener {
+    pub fn is_tcp(&self) -> bool {
+        match self {
+            Listener::Tcp(_) => true,
+            #[cfg(unix)]
+            Listener::Unix { .. } => false,
+            #[cfg(windows)]
+            Listener::Pipe(_) => false,
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Listener::Vsock(_) => false,
+        }
+    }
+
+    pub fn is_unix(&self) -> bool {
+        match self {
+            Listener::Tcp(_) => false,
+            #[cfg(unix)]
+            Listener::Unix { .. } => true,
+            #[cfg(windows)]
+            Listener::Pipe(_) => false,
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Listener::Vsock(_) => false,
+        }
+    }
+
+    pub fn transport(&self) -> Transport {
+        match self {
+            Listener::Tcp(_) => Transport::Tcp,
+            #[cfg(unix)]
+            Listener::Unix { .. } => Transport::Unix,
+            #[cfg(windows)]
+            Listener::Pipe(_) => Transport::Pipe,
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Listener::Vsock(_) => Transport::Vsock,
+        }
+    }
+
+    pub fn as_tcp(&self) -> Option<&TcpListener> {
+        match self {
+            Listener::Tcp(listener) => Some(listener),
+            #[cfg(unix)]
+            Listener::Unix { .. } => None,
+            #[cfg(windows)]
+            Listener::Pipe(_) => None,
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Listener::Vsock(_) => None,
+        }
+    }
+
+    pub fn as_tcp_mut(&mut self) -> Option<&mut TcpListener> {
+        match self {
+            Listener::Tcp(listener) => Some(listener),
+            #[cfg(unix)]
+            Listener::Unix { .. } => None,
+            #[cfg(windows)]
+            Listener::Pipe(_) => None,
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Listener::Vsock(_) => None,
+        }
+    }
+
+    #[cfg(unix)]
+    pub fn as_unix(&self) -> Option<&UnixListener> {
+        match self {
+            Listener::Unix { listener, .. } => Some(listener),
+            _ => None,
+        }
+    }
+
+    #[cfg(unix)]
+    pub fn as_unix_mut(&mut self) -> Option<&mut UnixListener> {
+        match self {
+            Listener::Unix { listener, .. } => Some(listener),
+            _ => None,
+        }
+    }
+
     // On non unix systems, remove and mode are not used.
     #[cfg_attr(not(unix), allow(unused_variables))]
     pub async fn bind_and_prepare_unix(named_socket_addr: &NamedSocketAddr, remove: bool, mode: Option<u32>) -> io::Result<Listener> {
+        Listener::bind_unix_with_owner(named_socket_addr, remove, mode, None, None).await
+    }
+
+    // On non unix systems, remove, mode, uid and gid are not used.
+    #[cfg_attr(not(unix), allow(unused_variables))]
+    pub async fn bind_unix_with_owner(
+        named_socket_addr: &NamedSocketAddr,
+        remove: bool,
+        mode: Option<u32>,
+        uid: Option<u32>,
+        gid: Option<u32>,
+    ) -> io::Result<Listener> {
         match named_socket_addr {
             NamedSocketAddr::Inet(inet_socket_addr) => {
                 TcpListener::bind(inet_socket_addr).await.map(Listener::Tcp)
             }
+            // NOTE: Inherited sockets are already bound, their file is left as it is.
+            #[cfg(unix)]
+            NamedSocketAddr::Unix(_) if named_socket_addr.inherited_fd().is_some() => {
+                Listener::bind(named_socket_addr).await
+            }
             #[cfg(unix)]
             NamedSocketAddr::Unix(path) => {
                 if remove && path.exists() {
                     fs::remove_file(path)?
                 }
 
-                let bound = UnixListener::bind(path)?;
+                let bound = bind_unix(path)?;
+
+                if uid.is_some() || gid.is_some() {
+                    if let Err(error) = chown(path, uid, gid) {
+                        // NOTE: Don't leave a socket file with the wrong owner behind.
+                        drop(bound);
+                        let _ = fs::remove_file(path);
+
+                        return Err(io::Error::new(
+                            error.kind(),
+                            format!("Cannot change owner of unix socket {:?}: {}", path, error),
+                        ))
+                    }
+                }
 
                 fs::set_permissions(
                     path,
                     Permissions::from_mode(mode.unwrap_or(0o222)),
                 )?;
 
-                Ok(Listener::Unix(bound))
+                Ok(Listener::from(bound))
+            }
+            #[cfg(not(unix))]
+            NamedSocketAddr::Unix(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot bind to a unix socket on a non unix system.",
+            )),
+            #[cfg(windows)]
+            NamedSocketAddr::Pipe(pipe_name) => NamedPipeListener::bind(pipe_name).map(Listener::Pipe),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            NamedSocketAddr::Vsock { cid, port } => VsockListener::bind(*cid, *port).map(Listener::Vsock),
+        }
+    }
+
+    // NOTE: Only creates the parent directories of the socket path, missing ones are created
+    //       with dir_mode (defaults to 0o755, subject to the umask). Existing directories are
+    //       left as they are. On non unix systems, dir_mode is not used.
+    #[cfg_attr(not(unix), allow(unused_variables))]
+    pub async fn bind_unix_mkdir(named_socket_addr: &NamedSocketAddr, dir_mode: Option<u32>) -> io::Result<Listener> {
+        #[cfg(unix)]
+        if let (NamedSocketAddr::Unix(path), None) = (named_socket_addr, named_socket_addr.inherited_fd()) {
+            if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+                fs::DirBuilder::new()
+                    .recursive(true)
+                    .mode(dir_mode.unwrap_or(0o755))
+                    .create(parent)?;
+            }
+        }
+
+        Listener::bind(named_socket_addr).await
+    }
+
+    pub async fn bind_in_range(ip: IpAddr, ports: RangeInclusive<u16>) -> io::Result<Listener> {
+        for port in ports {
+            match TcpListener::bind((ip, port)).await {
+                Ok(listener) => return Ok(Listener::Tcp(listener)),
+                Err(error) if error.kind() == io::ErrorKind::AddrInUse => continue,
+                Err(error) => return Err(error),
+            }
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            "Cannot bind to any port in the range.",
+        ))
+    }
+
+    // Binds to a port picked by the system and returns the concrete address alongside the
+    // listener. Only takes an IP address as Unix sockets have no ephemeral equivalent.
+    pub async fn bind_ephemeral(ip: IpAddr) -> io::Result<(Listener, SocketAddr)> {
+        let listener = Listener::Tcp(TcpListener::bind((ip, 0)).await?);
+        let local_addr = listener.local_addr()?;
+
+        Ok((listener, local_addr))
+    }
+
+    // NOTE: Sets IPV6_V6ONLY before binding, whose default differs between systems.
+    //       Binding an IPv6 address with only_v6 set to false accepts IPv4 connections too.
+    #[cfg(any(unix, windows))]
+    pub async fn bind_v6only(named_socket_addr: &NamedSocketAddr, only_v6: bool) -> io::Result<Listener> {
+        if !matches!(named_socket_addr, NamedSocketAddr::Inet(net::SocketAddr::V6(_))) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Cannot set IPV6_V6ONLY for an address that is not an IPv6 address.",
+            ))
+        }
+
+        let socket = Socket::new(Domain::IPV6, Type::STREAM, Some(Protocol::TCP))?;
+
+        socket.set_only_v6(only_v6)?;
+        // NOTE: Matches tokio::net::TcpListener::bind.
+        #[cfg(unix)]
+        socket.set_reuse_address(true)?;
+
+        Listener::from_socket2(socket, named_socket_addr)
+    }
+
+    // Binds a socket created and configured by the caller to the address, starts listening
+    // and registers it with the tokio reactor. Options affecting binding like SO_REUSEADDR
+    // have to be set on the socket before.
+    #[cfg(any(unix, windows))]
+    pub fn from_socket2(socket: Socket, named_socket_addr: &NamedSocketAddr) -> io::Result<Listener> {
+        #[cfg(unix)]
+        if named_socket_addr.inherited_fd().is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Cannot bind a socket2 socket to an inherited socket.",
+            ))
+        }
+
+        let sock_addr = match named_socket_addr {
+            NamedSocketAddr::Inet(inet_socket_addr) => SockAddr::from(*inet_socket_addr),
+            #[cfg(unix)]
+            NamedSocketAddr::Unix(path) => SockAddr::unix(path)?,
+            #[cfg(any(windows, all(feature = "vsock", target_os = "linux")))]
+            _ => return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot create a listener from a socket2 socket for this address.",
+            )),
+        };
+
+        // NOTE: The kind of listener follows the domain of the socket, which the address has
+        //       to match. Windows only supports tcp listeners here.
+        #[cfg(unix)]
+        let domain = fd::socket_domain(socket.as_raw_fd())?;
+
+        #[cfg(unix)]
+        if domain != libc::c_int::from(sock_addr.family()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Cannot bind a socket2 socket to an address of a different family.",
+            ))
+        }
+
+        socket.bind(&sock_addr)?;
+        socket.listen(1024)?;
+        socket.set_nonblocking(true)?;
+
+        #[cfg(unix)]
+        if domain == libc::AF_UNIX {
+            return UnixListener::from_std(OwnedFd::from(socket).into()).map(Listener::from)
+        }
+
+        TcpListener::from_std(socket.into()).map(Listener::Tcp)
+    }
+
+    // Like bind, but also accepts abstract unix socket names, see SocketAddr::to_bindable.
+    pub async fn bind_bindable(bindable_addr: &BindableAddr) -> io::Result<Listener> {
+        match bindable_addr {
+            BindableAddr::Named(named_socket_addr) => Listener::bind(named_socket_addr).await,
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            BindableAddr::Abstract(name) => {
+                // NOTE: Like tokio does for path sockets, the socket is created nonblocking and
+                //       close on exec atomically instead of toggling the flags afterwards.
+                let socket = Socket::new(Domain::UNIX, Type::from(libc::SOCK_STREAM | libc::SOCK_NONBLOCK), None)?;
+                let (sockaddr, length) = UnixSocketAddr::Abstract(name.clone()).to_sockaddr_bytes()?;
+
+                if unsafe { libc::bind(socket.as_raw_fd(), (&sockaddr as *const libc::sockaddr_un).cast(), length) } < 0 {
+                    return Err(io::Error::last_os_error())
+                }
+
+                socket.listen(1024)?;
+                UnixListener::from_std(OwnedFd::from(socket).into()).map(Listener::from)
             }
+            #[cfg(not(any(target_os = "linux", target_os = "android")))]
+            BindableAddr::Abstract(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot bind to an abstract unix socket on this system.",
+            )),
+        }
+    }
+
+    // Like bind_and_prepare_unix with remove set, but only removes an existing socket file
+    // if nothing is listening on it anymore. Binding to a live socket fails with AddrInUse.
+    pub async fn bind_unix_checked(named_socket_addr: &NamedSocketAddr, mode: Option<u32>) -> io::Result<BindOutcome> {
+        #[cfg(unix)]
+        if named_socket_addr.inherited_fd().is_some() {
+            return Ok(BindOutcome { listener: Listener::bind(named_socket_addr).await?, removed_stale: false })
+        }
+
+        let removed_stale = named_socket_addr.is_stale_unix_socket().await?;
+
+        #[cfg(unix)]
+        if let (true, NamedSocketAddr::Unix(path)) = (removed_stale, named_socket_addr) {
+            fs::remove_file(path)?;
         }
+
+        Ok(BindOutcome {
+            listener: Listener::bind_and_prepare_unix(named_socket_addr, false, mode).await?,
+            removed_stale,
+        })
     }
 
     pub async fn bind(named_socket_addr: &NamedSocketAddr) -> io::Result<Listener> {
@@ -71,21 +406,1436 @@ impl Listener {
                 TcpListener::bind(inet_socket_addr).await.map(Listener::Tcp)
             }
             #[cfg(unix)]
-            NamedSocketAddr::Unix(path) => UnixListener::bind(path).map(Listener::Unix),
+            NamedSocketAddr::Unix(path) => match named_socket_addr.inherited_fd() {
+                Some(fd) => Listener::from_inherited(fd),
+                None => bind_unix(path).map(Listener::from),
+            },
+            #[cfg(not(unix))]
+            NamedSocketAddr::Unix(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot bind to a unix socket on a non unix system.",
+            )),
+            #[cfg(windows)]
+            NamedSocketAddr::Pipe(pipe_name) => NamedPipeListener::bind(pipe_name).map(Listener::Pipe),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            NamedSocketAddr::Vsock { cid, port } => VsockListener::bind(*cid, *port).map(Listener::Vsock),
+        }
+    }
+
+    #[cfg(unix)]
+    pub fn set_permissions(&self, mode: u32) -> io::Result<()> {
+        match self {
+            Listener::Unix { listener, .. } => match listener.local_addr()?.as_pathname() {
+                Some(path) => fs::set_permissions(path, Permissions::from_mode(mode)),
+                None => Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "Cannot set permissions on a abstract or unnamed unix socket.",
+                )),
+            },
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot set permissions on a non unix listener.",
+            )),
+        }
+    }
+
+    // NOTE: The returned socket is still in nonblocking mode.
+    pub fn into_std(self) -> io::Result<StdListener> {
+        match self {
+            Listener::Tcp(listener) => listener.into_std().map(StdListener::Tcp),
+            #[cfg(unix)]
+            Listener::Unix { listener, .. } => listener.into_std().map(StdListener::Unix),
+            #[cfg(any(windows, all(feature = "vsock", target_os = "linux")))]
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot convert a listener without a std equivalent.",
+            )),
+        }
+    }
+
+    // NOTE: Unlike the tokio functions, this puts the socket into nonblocking mode.
+    pub fn from_std(std_listener: StdListener) -> io::Result<Listener> {
+        match std_listener {
+            StdListener::Tcp(listener) => {
+                listener.set_nonblocking(true)?;
+                TcpListener::from_std(listener).map(Listener::Tcp)
+            }
+            #[cfg(unix)]
+            StdListener::Unix(listener) => {
+                listener.set_nonblocking(true)?;
+                UnixListener::from_std(listener).map(Listener::from)
+            }
+        }
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        match self {
+            Listener::Tcp(listener) => listener.local_addr().map(SocketAddr::Inet),
+            #[cfg(unix)]
+            Listener::Unix { listener, .. } => Ok(SocketAddr::Unix(listener.local_addr()?.into())),
+            #[cfg(windows)]
+            Listener::Pipe(listener) => Ok(SocketAddr::Pipe(listener.pipe_name().to_path_buf())),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Listener::Vsock(listener) => listener
+                .local_addr()
+                .map(|vsock_addr| SocketAddr::Vsock { cid: vsock_addr.cid(), port: vsock_addr.port() }),
         }
     }
 
+    // Stops listening and, for Unix sockets bound to a path, removes the socket file, reporting
+    // errors instead of ignoring them like dropping the listener does. The file of sockets
+    // adopted from an inherited file descriptor is kept.
+    // NOTE: Duplicates of the listener (e.g. from try_clone) keep listening, but can't be
+    //       connected to through the path anymore.
+    pub fn close(self) -> io::Result<()> {
+        #[cfg(unix)]
+        let path = match self.local_addr()? {
+            SocketAddr::Unix(UnixSocketAddr::Pathname(path)) if !self.is_inherited() => Some(path),
+            _ => None,
+        };
+
+        drop(self);
+
+        #[cfg(unix)]
+        if let Some(path) = path {
+            match fs::symlink_metadata(&path) {
+                Ok(metadata) if metadata.file_type().is_socket() => fs::remove_file(&path)?,
+                Ok(_) => {}
+                Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+                Err(error) => return Err(error),
+            }
+        }
+
+        Ok(())
+    }
+
+    // Binds the new address like bind_and_prepare_unix and swaps it in, closing the old
+    // listener which removes its socket file. If binding fails the old listener is kept.
+    // NOTE: The new listener is already in place if removing the old socket file fails.
+    pub async fn rebind(&mut self, new_addr: &NamedSocketAddr, remove: bool, mode: Option<u32>) -> io::Result<()> {
+        let listener = Listener::bind_and_prepare_unix(new_addr, remove, mode).await?;
+        let old = mem::replace(self, listener);
+
+        // NOTE: Rebinding to the same path replaced the old socket file, removing it would
+        //       remove the new one. The paths are compared by the file they resolve to since
+        //       they may be spelled differently, e.g. relative to the working directory.
+        let same_path = match (old.local_addr(), new_addr) {
+            (Ok(SocketAddr::Unix(unix_socket_addr)), NamedSocketAddr::Unix(path)) => unix_socket_addr.as_pathname().is_some_and(|old_path| same_file(old_path, path)),
+            _ => false,
+        };
+
+        if same_path {
+            return Ok(())
+        }
+
+        old.close()
+    }
+
+    pub fn into_shared(self) -> SharedListener {
+        SharedListener::new(self)
+    }
+
     pub async fn accept(&self) -> io::Result<(Stream, SocketAddr)> {
+        let accept = async {
+            match self {
+                #[cfg(windows)]
+                Listener::Pipe(listener) => listener
+                    .accept()
+                    .await
+                    .map(|pipe_server| (Stream::PipeServer(pipe_server), SocketAddr::Pipe(listener.pipe_name().to_path_buf()))),
+                Listener::Tcp(listener) => listener
+                    .accept()
+                    .await
+                    .map(|(tcp_stream, inet_socket_addr)| (Stream::Tcp(tcp_stream), SocketAddr::Inet(inet_socket_addr))),
+                #[cfg(unix)]
+                Listener::Unix { listener, .. } => listener.accept().await.and_then(|(unix_stream, _)| accepted_unix(unix_stream)),
+                #[cfg(all(feature = "vsock", target_os = "linux"))]
+                Listener::Vsock(listener) => listener
+                    .accept()
+                    .await
+                    .map(|(vsock_stream, vsock_addr)| (Stream::Vsock(vsock_stream), SocketAddr::Vsock { cid: vsock_addr.cid(), port: vsock_addr.port() })),
+            }
+        };
+
+        // NOTE: See Stream::connect.
+        let accept = async {
+            let result = accept.await;
+
+            #[cfg(feature = "tracing")]
+            match &result {
+                Ok((_, socket_addr)) => tracing::debug!(transport = %self.transport(), peer_addr = %socket_addr, "Accepted connection"),
+                Err(error) => tracing::debug!(transport = %self.transport(), %error, "Cannot accept connection"),
+            }
+
+            result
+        };
+
+        #[cfg(feature = "tracing")]
+        let accept = accept.instrument(tracing::info_span!("accept", transport = %self.transport()));
+
+        accept.await
+    }
+
+    // NOTE: Named pipe listeners can only be accepted from asynchronously, use Listener::accept instead.
+    // NOTE: Like the tokio functions, only the task that polled last is woken up. Use
+    //       Listener::accept for accepting from multiple tasks, e.g. through SharedListener.
+    pub fn poll_accept(&self, cx: &mut Context<'_>) -> Poll<io::Result<(Stream, SocketAddr)>> {
         match self {
             Listener::Tcp(listener) => listener
-                .accept()
-                .await
-                .map(|(tcp_stream, inet_socket_addr)| (Stream::Tcp(tcp_stream), SocketAddr::Inet(inet_socket_addr))),
+                .poll_accept(cx)
+                .map_ok(|(tcp_stream, inet_socket_addr)| (Stream::Tcp(tcp_stream), SocketAddr::Inet(inet_socket_addr))),
             #[cfg(unix)]
-            Listener::Unix(listener) => listener
-                .accept()
-                .await
-                .map(|(unix_stream, unix_socket_addr)| (Stream::Unix(unix_stream), SocketAddr::Unix(unix_socket_addr.into()))),
+            Listener::Unix { listener, .. } => listener.poll_accept(cx).map(|result| accepted_unix(result?.0)),
+            #[cfg(windows)]
+            Listener::Pipe(_) => Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot poll a named pipe listener for connections.",
+            ))),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Listener::Vsock(listener) => listener
+                .poll_accept(cx)
+                .map_ok(|(vsock_stream, vsock_addr)| (Stream::Vsock(vsock_stream), SocketAddr::Vsock { cid: vsock_addr.cid(), port: vsock_addr.port() })),
+        }
+    }
+
+    // Waits for at least one connection, then accepts up to max connections that
+    // are already pending without waiting again, returning how many were added to out.
+    pub async fn accept_many(&self, max: usize, out: &mut Vec<(Stream, SocketAddr)>) -> io::Result<usize> {
+        #[cfg(windows)]
+        if let Listener::Pipe(_) = self {
+            if max == 0 {
+                return Ok(0);
+            }
+
+            out.push(self.accept().await?);
+            return Ok(1);
+        }
+
+        future::poll_fn(|cx| {
+            let mut accepted = 0;
+
+            while accepted < max {
+                match self.poll_accept(cx) {
+                    Poll::Ready(Ok(connection)) => {
+                        out.push(connection);
+                        accepted += 1;
+                    }
+                    Poll::Ready(Err(error)) if accepted == 0 => return Poll::Ready(Err(error)),
+                    // NOTE: Report the connections accepted so far, the error
+                    //       will surface again on the next call if it persists.
+                    Poll::Ready(Err(_)) => break,
+                    Poll::Pending if accepted == 0 => return Poll::Pending,
+                    Poll::Pending => break,
+                }
+            }
+
+            Poll::Ready(Ok(accepted))
+        })
+        .await
+    }
+
+    // Accepting as a stream of connections, telling errors affecting a single connection
+    // apart from errors after which the listener is unusable. See IncomingClassified.
+    pub fn incoming_classified(&self) -> IncomingClassified<'_> {
+        IncomingClassified::new(self)
+    }
+
+    // Like accept, but sleeps for backoff and retries after running out of file descriptors or
+    // memory instead of failing, which would make accept loops spin. Other errors are returned.
+    pub async fn accept_with_backoff(&self, backoff: Duration) -> io::Result<(Stream, SocketAddr)> {
+        loop {
+            match self.accept().await {
+                Err(error) if incoming::is_resource_exhaustion(&error) => time::sleep(backoff).await,
+                result => return result,
+            }
+        }
+    }
+
+    pub async fn accept_timeout(&self, duration: Duration) -> io::Result<Option<(Stream, SocketAddr)>> {
+        match time::timeout(duration, self.accept()).await {
+            Ok(result) => result.map(Some),
+            Err(_elapsed) => Ok(None),
+        }
+    }
+
+    // Options set on the listener aren't inherited by accepted connections, configure runs
+    // on every accepted stream before it is returned. If it fails the stream is closed.
+    pub async fn accept_with<F>(&self, configure: F) -> io::Result<(Stream, SocketAddr)>
+    where
+        F: FnOnce(&Stream) -> io::Result<()>,
+    {
+        let (stream, socket_addr) = self.accept().await?;
+
+        configure(&stream)?;
+
+        Ok((stream, socket_addr))
+    }
+
+    // NOTE: Connections are filtered after they have been accepted, for TCP the
+    //       handshake has already completed by then. Rejected connections are
+    //       closed by dropping them.
+    pub async fn accept_filtered<P>(&self, predicate: P) -> io::Result<(Stream, SocketAddr)>
+    where
+        P: Fn(&SocketAddr) -> bool,
+    {
+        loop {
+            let (stream, socket_addr) = self.accept().await?;
+
+            if predicate(&socket_addr) {
+                return Ok((stream, socket_addr));
+            }
+        }
+    }
+
+    // Accepts connections until the shutdown future resolves, then waits for all
+    // spawned handlers to complete.
+    pub async fn serve<S, F, Fut, E>(self, shutdown: S, handler: F, on_error: E)
+    where
+        S: Future<Output = ()>,
+        F: Fn(Stream, SocketAddr) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+        E: FnMut(io::Error),
+    {
+        self.serve_with_limit(Semaphore::MAX_PERMITS, shutdown, handler, on_error).await
+    }
+
+    // Like serve, but handles at most max_concurrent connections at once. While at
+    // capacity no connections are accepted, leaving them queued in the backlog.
+    // NOTE: After an error affecting a single connection, e.g. running out of file
+    //       descriptors, accepting is retried after a short backoff so the loop doesn't spin.
+    //       After a fatal error, see AcceptError, no more connections are accepted and the
+    //       spawned handlers are waited for like on shutdown. on_error is called each time.
+    // NOTE: A max_concurrent of 0 would never accept anything. It's passed to on_error as an
+    //       io::ErrorKind::InvalidInput error instead and nothing is accepted.
+    pub async fn serve_with_limit<S, F, Fut, E>(self, max_concurrent: usize, shutdown: S, handler: F, on_error: E)
+    where
+        S: Future<Output = ()>,
+        F: Fn(Stream, SocketAddr) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+        E: FnMut(io::Error),
+    {
+        self.serve_with_limit_and_backoff(max_concurrent, incoming::DEFAULT_ACCEPT_BACKOFF, shutdown, handler, on_error).await
+    }
+
+    // Like serve_with_limit, but waits for backoff instead of 100ms before accepting again
+    // after an error. Duration::ZERO retries right away.
+    pub async fn serve_with_limit_and_backoff<S, F, Fut, E>(
+        self,
+        max_concurrent: usize,
+        backoff: Duration,
+        shutdown: S,
+        handler: F,
+        mut on_error: E,
+    )
+    where
+        S: Future<Output = ()>,
+        F: Fn(Stream, SocketAddr) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+        E: FnMut(io::Error),
+    {
+        if max_concurrent == 0 {
+            return on_error(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Cannot serve with a limit of 0 concurrent connections.",
+            ))
+        }
+
+        serve_source(self, max_concurrent, backoff, shutdown, handler, on_error).await
+    }
+}
+
+// NOTE: The loop behind Listener::serve, generic over the source so tests can inject
+//       accept errors.
+async fn serve_source<C, S, F, Fut, E>(source: C, max_concurrent: usize, backoff: Duration, shutdown: S, handler: F, mut on_error: E)
+where
+    C: ConnectionSource,
+    S: Future<Output = ()>,
+    F: Fn(Stream, SocketAddr) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+    E: FnMut(io::Error),
+{
+    let semaphore = Arc::new(Semaphore::new(max_concurrent));
+    let mut handlers = JoinSet::new();
+
+    tokio::pin!(shutdown);
+
+    // NOTE: A deadline instead of a duration, so the backoff isn't restarted or skipped
+    //       when a handler completing wins the select below.
+    let mut resume_at = None;
+
+    loop {
+        let deadline = resume_at;
+
+        let accept = async {
+            if let Some(deadline) = deadline {
+                time::sleep_until(deadline).await;
+            }
+
+            let permit = Arc::clone(&semaphore).acquire_owned().await.expect("Cannot acquire permit from a closed semaphore");
+
+            (permit, source.accept().await)
+        };
+
+        tokio::select! {
+            _ = &mut shutdown => break,
+            (permit, result) = accept => match result.map_err(AcceptError::classify) {
+                Ok((stream, socket_addr)) => {
+                    resume_at = None;
+
+                    let handle = handler(stream, socket_addr);
+
+                    handlers.spawn(async move {
+                        handle.await;
+                        drop(permit);
+                    });
+                }
+                Err(AcceptError::PerConnection(error)) => {
+                    resume_at = Some(Instant::now() + backoff);
+
+                    on_error(error)
+                }
+                Err(AcceptError::Fatal(error)) => {
+                    on_error(error);
+
+                    break
+                }
+            },
+            Some(_) = handlers.join_next(), if !handlers.is_empty() => {}
+        }
+    }
+
+    drop(source);
+
+    while handlers.join_next().await.is_some() {}
+}
+
+#[cfg(unix)]
+impl Listener {
+    /// # Safety
+    ///
+    /// The file descriptor must be an open, listening TCP socket that is not
+    /// owned by anything else, ownership is transferred to the returned listener.
+    pub unsafe fn from_raw_tcp_fd(fd: RawFd) -> io::Result<Listener> {
+        Listener::from_std(StdListener::Tcp(net::TcpListener::from_raw_fd(fd)))
+    }
+
+    /// # Safety
+    ///
+    /// The file descriptor must be an open, listening Unix stream socket that is
+    /// not owned by anything else, ownership is transferred to the returned listener.
+    pub unsafe fn from_raw_unix_fd(fd: RawFd) -> io::Result<Listener> {
+        Listener::from_std(StdListener::Unix(unix::net::UnixListener::from_raw_fd(fd)))
+    }
+
+    /// # Safety
+    ///
+    /// The file descriptor must be an open, listening TCP, Unix or vsock stream socket
+    /// that is not owned by anything else, ownership is transferred to the returned listener.
+    /// The file descriptor is closed if this fails.
+    // NOTE: See Stream::from_raw_socket_fd.
+    pub unsafe fn from_raw_socket_fd(fd: RawFd) -> io::Result<Listener> {
+        match fd::socket_domain(fd) {
+            Ok(domain) => Listener::from_raw_fd_with_domain(fd, domain),
+            Err(error) => {
+                libc::close(fd);
+                Err(error)
+            }
+        }
+    }
+
+    // NOTE: See Stream::set_cloexec.
+    pub fn set_cloexec(&self, cloexec: bool) -> io::Result<()> {
+        fd::set_cloexec(self.as_raw_fd(), cloexec)
+    }
+
+    // NOTE: See Stream::set_nonblocking.
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        fd::set_nonblocking(self.as_raw_fd(), nonblocking)
+    }
+
+    // Like bind, but sets the type of service (IP_TOS, or IPV6_TCLASS for IPv6) before binding.
+    // NOTE: On Linux accepted connections inherit the value, elsewhere it may have to be set
+    //       on each accepted stream using Stream::set_tos.
+    pub async fn bind_with_tos(named_socket_addr: &NamedSocketAddr, tos: u32) -> io::Result<Listener> {
+        let inet_socket_addr = match named_socket_addr {
+            NamedSocketAddr::Inet(inet_socket_addr) => inet_socket_addr,
+            _ => return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot set the type of service of a non tcp listener.",
+            )),
+        };
+
+        let socket = Socket::new(Domain::for_address(*inet_socket_addr), Type::STREAM, Some(Protocol::TCP))?;
+
+        fd::set_tos(socket.as_raw_fd(), inet_socket_addr.is_ipv6(), tos)?;
+        // NOTE: Matches tokio::net::TcpListener::bind.
+        socket.set_reuse_address(true)?;
+
+        Listener::from_socket2(socket, named_socket_addr)
+    }
+
+    unsafe fn from_raw_fd_with_domain(fd: RawFd, domain: libc::c_int) -> io::Result<Listener> {
+        match domain {
+            libc::AF_UNIX => Listener::from_raw_unix_fd(fd),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            libc::AF_VSOCK => VsockListener::from_raw_fd(fd).map(Listener::Vsock),
+            _ => Listener::from_raw_tcp_fd(fd),
+        }
+    }
+
+    // Adopts a duplicate of fd, which stays owned by the caller.
+    fn from_duplicate_of(fd: RawFd) -> io::Result<Listener> {
+        let domain = fd::socket_domain(fd)?;
+        let duplicate = fd::duplicate(fd)?;
+
+        unsafe { Listener::from_raw_fd_with_domain(duplicate, domain) }
+    }
+
+    // NOTE: See Stream::try_clone. Connections are accepted by whichever handle polls first.
+    pub fn try_clone(&self) -> io::Result<Listener> {
+        Listener::from_duplicate_of(self.as_raw_fd()).map(|listener| listener.with_inherited(self.is_inherited()))
+    }
+
+    // See NamedSocketAddr::inherited_fd.
+    fn from_inherited(fd: RawFd) -> io::Result<Listener> {
+        fd::check_inherited(fd, true)?;
+
+        Listener::from_duplicate_of(fd).map(|listener| listener.with_inherited(true))
+    }
+
+    fn with_inherited(self, inherited: bool) -> Listener {
+        match self {
+            Listener::Unix { listener, .. } => Listener::Unix { listener, inherited },
+            listener => listener,
+        }
+    }
+
+    fn is_inherited(&self) -> bool {
+        matches!(self, Listener::Unix { inherited: true, .. })
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Listener {
+    // NOTE: This only sets the mark of the listening socket. Whether accepted sockets
+    //       inherit it depends on the kernel and the transport, set the mark on the
+    //       accepted Stream where it matters.
+    pub fn set_mark(&self, mark: u32) -> io::Result<()> {
+        fd::set_mark(self.as_raw_fd(), mark)
+    }
+
+    pub fn mark(&self) -> io::Result<u32> {
+        fd::mark(self.as_raw_fd())
+    }
+
+    // Restricts the listener to connections arriving over the given network interface.
+    pub fn bind_to_device(&self, interface: &str) -> io::Result<()> {
+        match self {
+            Listener::Tcp(tcp_listener) => fd::bind_to_device(tcp_listener.as_raw_fd(), interface),
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot bind a non tcp listener to a device.",
+            )),
         }
     }
+
+    // Like bind, but sets SO_BINDTODEVICE before binding. This allows binding the same
+    // address on several interfaces.
+    pub async fn bind_with_device(named_socket_addr: &NamedSocketAddr, interface: &str) -> io::Result<Listener> {
+        let inet_socket_addr = match named_socket_addr {
+            NamedSocketAddr::Inet(inet_socket_addr) => inet_socket_addr,
+            _ => return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot bind a non tcp listener to a device.",
+            )),
+        };
+
+        let socket = Socket::new(Domain::for_address(*inet_socket_addr), Type::STREAM, Some(Protocol::TCP))?;
+
+        fd::bind_to_device(socket.as_raw_fd(), interface)?;
+        // NOTE: Matches tokio::net::TcpListener::bind.
+        socket.set_reuse_address(true)?;
+
+        Listener::from_socket2(socket, named_socket_addr)
+    }
+}
+
+#[cfg(unix)]
+impl AsRawFd for Listener {
+    fn as_raw_fd(&self) -> RawFd {
+        match self {
+            Listener::Tcp(listener) => listener.as_raw_fd(),
+            Listener::Unix { listener, .. } => listener.as_raw_fd(),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Listener::Vsock(listener) => listener.as_raw_fd(),
+        }
+    }
+}
+
+#[cfg(unix)]
+impl AsFd for Listener {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // SAFETY: The file descriptor stays open as long as the listener is borrowed.
+        unsafe { BorrowedFd::borrow_raw(self.as_raw_fd()) }
+    }
+}
+
+// NOTE: See the IntoRawFd implementation of StdStream.
+#[cfg(unix)]
+impl IntoRawFd for StdListener {
+    fn into_raw_fd(self) -> RawFd {
+        match self {
+            StdListener::Tcp(listener) => listener.into_raw_fd(),
+            StdListener::Unix(listener) => listener.into_raw_fd(),
+        }
+    }
+}
+
+// NOTE: Binding fails with AddrInUse as long as the socket file exists, even if the process
+//       that created it exited without removing it.
+// NOTE: tokio creates the socket with SOCK_NONBLOCK and SOCK_CLOEXEC and accepts using accept4
+//       with the same flags, so neither the listener nor accepted streams leak into processes
+//       spawned concurrently. Systems without these flags, e.g. macOS, set them using fcntl
+//       right after creating the socket, which can't be avoided there.
+#[cfg(unix)]
+fn bind_unix(path: &Path) -> io::Result<UnixListener> {
+    UnixListener::bind(path).map_err(|error| match error.kind() {
+        io::ErrorKind::AddrInUse => io::Error::new(
+            error.kind(),
+            format!(
+                "Cannot bind to unix socket {:?}: {}. If it is stale, remove it or bind using bind_unix_checked or bind_and_prepare_unix with remove set.",
+                path, error,
+            ),
+        ),
+        _ => error,
+    })
+}
+
+// NOTE: The address returned by tokio when accepting loses abstract names,
+//       ask the accepted stream for its peer instead.
+#[cfg(unix)]
+fn accepted_unix(unix_stream: UnixStream) -> io::Result<(Stream, SocketAddr)> {
+    let unix_socket_addr = unix_stream.peer_addr()?;
+
+    Ok((Stream::Unix(unix_stream), SocketAddr::Unix(unix_socket_addr.into())))
+}
+
+// NOTE: Compares device and inode numbers rather than the paths themselves, which may be
+//       relative, contain symlinks or differ otherwise while naming the same socket file.
+#[cfg(unix)]
+fn same_file(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    match (fs::metadata(a), fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn same_file(a: &std::path::Path, b: &std::path::Path) -> bool {
+    match (std::fs::canonicalize(a), std::fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    #[tokio::test]
+    async fn tcp_accessors() {
+        let (mut listener, _) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+
+        assert!(listener.is_tcp());
+        assert!(!listener.is_unix());
+        assert_eq!(listener.transport(), Transport::Tcp);
+        assert!(listener.as_tcp().is_some());
+        assert!(listener.as_tcp_mut().is_some());
+        #[cfg(unix)]
+        assert!(listener.as_unix().is_none());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn unix_accessors() {
+        let path = test_util::temp_path("accessors.sock");
+        let mut listener = Listener::bind(&NamedSocketAddr::unix(&path)).await.unwrap();
+
+        assert!(listener.is_unix());
+        assert!(!listener.is_tcp());
+        assert_eq!(listener.transport(), Transport::Unix);
+        assert!(listener.as_unix().is_some());
+        assert!(listener.as_unix_mut().is_some());
+        assert!(listener.as_tcp().is_none());
+
+        listener.close().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn set_permissions() {
+        let path = test_util::temp_path("permissions.sock");
+        let listener = Listener::bind(&NamedSocketAddr::unix(&path)).await.unwrap();
+
+        listener.set_permissions(0o600).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+
+        listener.close().unwrap();
+
+        let (tcp_listener, _) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+
+        assert_eq!(tcp_listener.set_permissions(0o600).unwrap_err().kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn bind_with_owner() {
+        use std::os::unix::fs::MetadataExt;
+
+        let path = test_util::temp_path("owner.sock");
+        let named_socket_addr = NamedSocketAddr::unix(&path);
+
+        // NOTE: A fresh file in the same directory is owned by the user and group new sockets get.
+        let owner_path = test_util::temp_path("owner.file");
+        fs::write(&owner_path, []).unwrap();
+        let owner_metadata = fs::metadata(&owner_path).unwrap();
+        let (uid, gid) = (owner_metadata.uid(), owner_metadata.gid());
+        fs::remove_file(&owner_path).unwrap();
+
+        let listener = Listener::bind_unix_with_owner(&named_socket_addr, false, Some(0o660), Some(uid), Some(gid)).await.unwrap();
+        let metadata = fs::metadata(&path).unwrap();
+
+        assert_eq!((metadata.uid(), metadata.gid()), (uid, gid));
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o660);
+
+        listener.close().unwrap();
+
+        // NOTE: Only root may give files away, otherwise the socket file is removed again.
+        let result = Listener::bind_unix_with_owner(&named_socket_addr, false, None, Some(65534), None).await;
+
+        if uid == 0 {
+            assert_eq!(fs::metadata(&path).unwrap().uid(), 65534);
+            result.unwrap().close().unwrap();
+        } else {
+            assert_eq!(result.unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+            assert!(!path.exists());
+        }
+    }
+
+    #[tokio::test]
+    async fn std_round_trip() {
+        let (listener, local_addr) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+
+        #[cfg_attr(windows, allow(irrefutable_let_patterns))]
+        let StdListener::Tcp(std_listener) = listener.into_std().unwrap() else { panic!("expected a tcp listener") };
+
+        assert_eq!(std_listener.accept().unwrap_err().kind(), io::ErrorKind::WouldBlock);
+
+        let listener = Listener::from_std(StdListener::Tcp(std_listener)).unwrap();
+
+        assert_eq!(listener.local_addr().unwrap(), local_addr);
+        test_util::connect_to(&listener).await;
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn raw_fd_round_trip() {
+        let path = test_util::temp_path("raw_fd.sock");
+        let (tcp_listener, _) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+        let unix_listener = Listener::bind(&NamedSocketAddr::unix(&path)).await.unwrap();
+
+        for listener in [tcp_listener, unix_listener] {
+            let local_addr = listener.local_addr().unwrap();
+            let listener = unsafe { Listener::from_raw_socket_fd(listener.into_std().unwrap().into_raw_fd()) }.unwrap();
+
+            assert_eq!(listener.local_addr().unwrap(), local_addr);
+            test_util::connect_to(&listener).await;
+        }
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(not(unix))]
+    #[tokio::test]
+    async fn unix_unsupported() {
+        let named_socket_addr = NamedSocketAddr::unix("app.sock");
+
+        assert_eq!(Listener::bind(&named_socket_addr).await.unwrap_err().kind(), io::ErrorKind::Unsupported);
+        assert_eq!(Stream::connect(&named_socket_addr).await.unwrap_err().kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[tokio::test]
+    async fn accept_timeout() {
+        let (listener, local_addr) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+
+        assert!(listener.accept_timeout(Duration::from_millis(10)).await.unwrap().is_none());
+
+        let named_socket_addr = local_addr.to_named_socket_addr().unwrap();
+        let (client, accepted) = tokio::join!(Stream::connect(&named_socket_addr), listener.accept_timeout(Duration::from_secs(5)));
+        let (_, peer_addr) = accepted.unwrap().expect("connection before the deadline");
+
+        assert_eq!(client.unwrap().local_addr().unwrap(), peer_addr);
+    }
+
+    #[tokio::test]
+    async fn serve_drains_handlers() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        use tokio::io::AsyncReadExt;
+        use tokio::sync::{mpsc, oneshot};
+
+        let (listener, local_addr) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+        let named_socket_addr = local_addr.to_named_socket_addr().unwrap();
+
+        let finished = Arc::new(AtomicUsize::new(0));
+        let (started_sender, mut started_receiver) = mpsc::unbounded_channel();
+        let (shutdown_sender, shutdown_receiver) = oneshot::channel::<()>();
+
+        let handler_finished = Arc::clone(&finished);
+        let mut serve = tokio::spawn(listener.serve(
+            async move { let _ = shutdown_receiver.await; },
+            move |mut stream, _| {
+                let (started_sender, finished) = (started_sender.clone(), Arc::clone(&handler_finished));
+
+                async move {
+                    started_sender.send(()).unwrap();
+
+                    // NOTE: Keeps the handler running until the client closes the connection.
+                    let _ = stream.read_to_end(&mut Vec::new()).await;
+
+                    finished.fetch_add(1, Ordering::SeqCst);
+                }
+            },
+            |error| panic!("unexpected accept error: {}", error),
+        ));
+
+        let mut clients = Vec::new();
+
+        for _ in 0..3 {
+            clients.push(Stream::connect(&named_socket_addr).await.unwrap());
+            started_receiver.recv().await.unwrap();
+        }
+
+        shutdown_sender.send(()).unwrap();
+
+        // NOTE: The handlers are still running, so serve doesn't return yet.
+        assert!(time::timeout(Duration::from_millis(50), &mut serve).await.is_err());
+        assert_eq!(finished.load(Ordering::SeqCst), 0);
+
+        drop(clients);
+
+        serve.await.unwrap();
+        assert_eq!(finished.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn accept_many() {
+        let (listener, local_addr) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+        let named_socket_addr = local_addr.to_named_socket_addr().unwrap();
+        let mut clients = Vec::new();
+
+        // NOTE: Connecting completes once the kernel queued the connection in the backlog.
+        for _ in 0..3 {
+            clients.push(Stream::connect(&named_socket_addr).await.unwrap());
+        }
+
+        let mut accepted = Vec::new();
+
+        assert_eq!(listener.accept_many(0, &mut accepted).await.unwrap(), 0);
+        assert_eq!(listener.accept_many(2, &mut accepted).await.unwrap(), 2);
+        assert_eq!(listener.accept_many(5, &mut accepted).await.unwrap(), 1);
+
+        let mut peer_addrs = accepted.iter().map(|(_, peer_addr)| peer_addr.clone()).collect::<Vec<_>>();
+        let mut local_addrs = clients.iter().map(|client| client.local_addr().unwrap()).collect::<Vec<_>>();
+
+        peer_addrs.sort();
+        local_addrs.sort();
+        assert_eq!(peer_addrs, local_addrs);
+    }
+
+    #[tokio::test]
+    async fn accept_filtered() {
+        use tokio::io::AsyncReadExt;
+
+        let (listener, local_addr) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+        let named_socket_addr = local_addr.to_named_socket_addr().unwrap();
+
+        let mut rejected = Stream::connect(&named_socket_addr).await.unwrap();
+        let accepted_client = Stream::connect(&named_socket_addr).await.unwrap();
+        let accepted_addr = accepted_client.local_addr().unwrap();
+
+        let (_, peer_addr) = listener.accept_filtered(|socket_addr| socket_addr == &accepted_addr).await.unwrap();
+
+        assert_eq!(peer_addr, accepted_addr);
+        // NOTE: The rejected connection was closed by dropping it.
+        assert_eq!(rejected.read(&mut [0; 1]).await.unwrap(), 0);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn bind_unix_checked() {
+        let path = test_util::temp_path("checked.sock");
+        let named_socket_addr = NamedSocketAddr::unix(&path);
+
+        // NOTE: Without a socket file there's nothing to remove.
+        let outcome = Listener::bind_unix_checked(&named_socket_addr, None).await.unwrap();
+
+        assert!(!outcome.removed_stale);
+
+        // NOTE: A live socket is never replaced.
+        let error = Listener::bind_unix_checked(&named_socket_addr, None).await.unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::AddrInUse);
+        test_util::connect_to(&outcome.listener).await;
+
+        // NOTE: Dropping the listener without closing it leaves a stale socket file behind.
+        drop(outcome);
+        assert!(path.exists());
+
+        let outcome = Listener::bind_unix_checked(&named_socket_addr, Some(0o600)).await.unwrap();
+
+        assert!(outcome.removed_stale);
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        test_util::connect_to(&outcome.listener).await;
+
+        outcome.listener.close().unwrap();
+    }
+
+    #[cfg(any(unix, windows))]
+    #[tokio::test]
+    async fn bind_v6only() {
+        let unspecified = NamedSocketAddr::Inet((net::Ipv6Addr::UNSPECIFIED, 0).into());
+
+        let dual_stack = Listener::bind_v6only(&unspecified, false).await.unwrap();
+        let port = dual_stack.local_addr().unwrap().as_inet().unwrap().port();
+        let ipv4_socket_addr = NamedSocketAddr::Inet((test_util::LOCALHOST, port).into());
+
+        let (client, accepted) = tokio::join!(Stream::connect(&ipv4_socket_addr), dual_stack.accept());
+
+        client.unwrap();
+        accepted.unwrap();
+
+        let only_v6 = Listener::bind_v6only(&unspecified, true).await.unwrap();
+        let port = only_v6.local_addr().unwrap().as_inet().unwrap().port();
+        let ipv4_socket_addr = NamedSocketAddr::Inet((test_util::LOCALHOST, port).into());
+
+        assert_eq!(Stream::connect(&ipv4_socket_addr).await.unwrap_err().kind(), io::ErrorKind::ConnectionRefused);
+
+        let error = Listener::bind_v6only(&NamedSocketAddr::Inet((test_util::LOCALHOST, 0).into()), true).await.unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn bind_inherited_fd() {
+        let path = test_util::temp_path("inherited.sock");
+        let named_socket_addr = NamedSocketAddr::unix(&path);
+        let listener = Listener::bind(&named_socket_addr).await.unwrap();
+        let inherited = Listener::bind(&NamedSocketAddr::from_fd(listener.as_raw_fd())).await.unwrap();
+
+        assert_eq!(inherited.local_addr().unwrap(), listener.local_addr().unwrap());
+
+        let (client, accepted) = tokio::join!(Stream::connect(&named_socket_addr), inherited.accept());
+
+        client.unwrap();
+        accepted.unwrap();
+
+        // NOTE: The socket file belongs to whoever passed the socket on.
+        inherited.try_clone().unwrap().close().unwrap();
+        inherited.close().unwrap();
+        assert!(path.exists());
+
+        let udp_socket = net::UdpSocket::bind((test_util::LOCALHOST, 0)).unwrap();
+        let error = Listener::bind(&NamedSocketAddr::from_fd(udp_socket.as_raw_fd())).await.unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+
+        let (client, _server) = test_util::tcp_pair().await;
+        let error = Listener::bind(&NamedSocketAddr::from_fd(client.as_raw_fd())).await.unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+
+        // NOTE: The listener the socket was inherited from still owns the file.
+        listener.close().unwrap();
+        assert!(!path.exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn set_cloexec() {
+        let (listener, _) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+
+        listener.set_cloexec(false).unwrap();
+        assert!(!test_util::is_cloexec(listener.as_raw_fd()));
+
+        listener.set_cloexec(true).unwrap();
+        assert!(test_util::is_cloexec(listener.as_raw_fd()));
+    }
+
+    #[tokio::test]
+    async fn bind_in_range() {
+        let (_taken, local_addr) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+        let taken_port = local_addr.as_inet().unwrap().port();
+
+        let error = Listener::bind_in_range(test_util::LOCALHOST, taken_port..=taken_port).await.unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::AddrInUse);
+
+        // NOTE: Skips the port in use, assuming one of the next ones is free.
+        let last_port = taken_port.saturating_add(16);
+        let listener = Listener::bind_in_range(test_util::LOCALHOST, taken_port..=last_port).await.unwrap();
+        let port = listener.local_addr().unwrap().as_inet().unwrap().port();
+
+        assert!(port > taken_port && port <= last_port);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn abstract_and_unnamed_addrs() {
+        use std::os::linux::net::SocketAddrExt;
+
+        use crate::StdStream;
+
+        let name = format!("tokio-unix-tcp-{}-abstract", std::process::id()).into_bytes();
+        let listener = Listener::bind_bindable(&BindableAddr::Abstract(name.clone())).await.unwrap();
+
+        assert_eq!(listener.local_addr().unwrap(), SocketAddr::Unix(UnixSocketAddr::Abstract(name.clone())));
+
+        let abstract_addr = unix::net::SocketAddr::from_abstract_name(&name).unwrap();
+        let client = Stream::from_std(StdStream::Unix(unix::net::UnixStream::connect_addr(&abstract_addr).unwrap())).unwrap();
+        let (_, peer_addr) = listener.accept().await.unwrap();
+
+        assert_eq!(client.peer_addr().unwrap(), SocketAddr::Unix(UnixSocketAddr::Abstract(name)));
+        assert_eq!(client.local_addr().unwrap(), SocketAddr::Unix(UnixSocketAddr::Unnamed));
+        assert_eq!(peer_addr, SocketAddr::Unix(UnixSocketAddr::Unnamed));
+
+        let error = peer_addr.to_named_socket_addr().unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[tokio::test]
+    async fn serve_with_limit() {
+        use tokio::io::AsyncReadExt;
+        use tokio::sync::{mpsc, oneshot};
+
+        let (listener, local_addr) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+        let named_socket_addr = local_addr.to_named_socket_addr().unwrap();
+
+        let (started_sender, mut started_receiver) = mpsc::unbounded_channel();
+        let (shutdown_sender, shutdown_receiver) = oneshot::channel::<()>();
+
+        let serve = tokio::spawn(listener.serve_with_limit(
+            1,
+            async move { let _ = shutdown_receiver.await; },
+            move |mut stream, socket_addr| {
+                let started_sender = started_sender.clone();
+
+                async move {
+                    started_sender.send(socket_addr).unwrap();
+                    let _ = stream.read_to_end(&mut Vec::new()).await;
+                }
+            },
+            |error| panic!("unexpected accept error: {}", error),
+        ));
+
+        let first = Stream::connect(&named_socket_addr).await.unwrap();
+        let second = Stream::connect(&named_socket_addr).await.unwrap();
+
+        assert_eq!(started_receiver.recv().await.unwrap(), first.local_addr().unwrap());
+
+        // NOTE: The second connection waits in the backlog while the first is handled.
+        assert!(time::timeout(Duration::from_millis(50), started_receiver.recv()).await.is_err());
+
+        drop(first);
+        assert_eq!(started_receiver.recv().await.unwrap(), second.local_addr().unwrap());
+
+        shutdown_sender.send(()).unwrap();
+        drop(second);
+        serve.await.unwrap();
+
+        let (listener, _) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+        let mut errors = Vec::new();
+
+        listener.serve_with_limit(0, future::pending(), |_, _| async {}, |error| errors.push(error.kind())).await;
+
+        assert_eq!(errors, [io::ErrorKind::InvalidInput]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn serve_backs_off_on_repeated_emfile() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        use crate::AcceptFuture;
+
+        // NOTE: Fails every accept like a process that ran out of file descriptors.
+        struct Exhausted(Arc<AtomicUsize>);
+
+        impl ConnectionSource for Exhausted {
+            fn accept(&self) -> AcceptFuture<'_> {
+                self.0.fetch_add(1, Ordering::Relaxed);
+
+                Box::pin(async { Err(io::Error::from_raw_os_error(libc::EMFILE)) })
+            }
+        }
+
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let mut errors = 0;
+
+        serve_source(
+            Exhausted(Arc::clone(&attempts)),
+            1,
+            Duration::from_millis(20),
+            time::sleep(Duration::from_millis(200)),
+            |_, _| async {},
+            |error| {
+                assert_eq!(error.raw_os_error(), Some(libc::EMFILE));
+                errors += 1;
+            },
+        ).await;
+
+        let attempts = attempts.load(Ordering::Relaxed);
+
+        // NOTE: Retrying right away would accept thousands of times within the 200ms.
+        assert!((2..=11).contains(&attempts), "accepted {} times", attempts);
+        assert_eq!(errors, attempts);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn set_mark() {
+        let (listener, _) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+
+        // NOTE: Setting a mark requires CAP_NET_ADMIN.
+        match listener.set_mark(7) {
+            Err(error) if error.kind() == io::ErrorKind::PermissionDenied => return,
+            result => result.unwrap(),
+        }
+
+        assert_eq!(listener.mark().unwrap(), 7);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn bind_with_device() {
+        let named_socket_addr = NamedSocketAddr::Inet((test_util::LOCALHOST, 0).into());
+
+        // NOTE: Binding to a device requires CAP_NET_RAW.
+        let listener = match Listener::bind_with_device(&named_socket_addr, "lo").await {
+            Err(error) if error.kind() == io::ErrorKind::PermissionDenied => return,
+            result => result.unwrap(),
+        };
+
+        let (client, _server) = test_util::connect_to(&listener).await;
+
+        client.bind_to_device("lo").unwrap();
+        assert!(listener.bind_to_device("tokio-unix-tcp0").is_err());
+
+        let unix_socket_addr = NamedSocketAddr::unix(test_util::temp_path("device.sock"));
+        let error = Listener::bind_with_device(&unix_socket_addr, "lo").await.unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+        assert_eq!(test_util::unix_pair().0.bind_to_device("lo").unwrap_err().kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn bind_unix_in_use() {
+        let path = test_util::temp_path("in_use.sock");
+        let named_socket_addr = NamedSocketAddr::unix(&path);
+        let listener = Listener::bind(&named_socket_addr).await.unwrap();
+
+        // NOTE: The stale file left behind by dropping the listener is still in use.
+        drop(listener);
+
+        let error = Listener::bind(&named_socket_addr).await.unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::AddrInUse);
+        assert!(error.to_string().contains(&format!("{:?}", path)));
+        assert!(error.to_string().contains("bind_unix_checked"));
+
+        fs::remove_file(&path).unwrap();
+        Listener::bind(&named_socket_addr).await.unwrap().close().unwrap();
+    }
+
+    #[tokio::test]
+    async fn from_socket2() {
+        let socket = Socket::new(Domain::IPV4, Type::STREAM, Some(Protocol::TCP)).unwrap();
+
+        socket.set_recv_buffer_size(65536).unwrap();
+
+        let listener = Listener::from_socket2(socket, &NamedSocketAddr::from((test_util::LOCALHOST, 0))).unwrap();
+
+        assert_eq!(listener.transport(), Transport::Tcp);
+        test_util::connect_to(&listener).await;
+
+        #[cfg(unix)]
+        {
+            let path = test_util::temp_path("socket2.sock");
+            let socket = Socket::new(Domain::UNIX, Type::STREAM, None).unwrap();
+            let listener = Listener::from_socket2(socket, &NamedSocketAddr::unix(&path)).unwrap();
+
+            assert_eq!(listener.transport(), Transport::Unix);
+            assert_eq!(listener.local_addr().unwrap(), SocketAddr::unix(&path));
+            test_util::connect_to(&listener).await;
+
+            listener.close().unwrap();
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn set_nonblocking() {
+        let (listener, _) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+
+        assert!(test_util::is_nonblocking(listener.as_raw_fd()));
+
+        listener.set_nonblocking(false).unwrap();
+        assert!(!test_util::is_nonblocking(listener.as_raw_fd()));
+
+        listener.set_nonblocking(true).unwrap();
+        test_util::connect_to(&listener).await;
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn try_clone() {
+        let (listener, local_addr) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+        let cloned = listener.try_clone().unwrap();
+
+        assert_ne!(cloned.as_raw_fd(), listener.as_raw_fd());
+        assert!(test_util::is_cloexec(cloned.as_raw_fd()));
+        assert_eq!(cloned.local_addr().unwrap(), local_addr);
+
+        drop(listener);
+        test_util::connect_to(&cloned).await;
+    }
+
+    #[tokio::test]
+    async fn bind_ephemeral() {
+        let (listener, local_addr) = Listener::bind_ephemeral(IpAddr::V4(net::Ipv4Addr::UNSPECIFIED)).await.unwrap();
+
+        assert_eq!(local_addr, listener.local_addr().unwrap());
+        assert_eq!(local_addr.ip(), Some(IpAddr::V4(net::Ipv4Addr::UNSPECIFIED)));
+        assert_ne!(local_addr.port(), Some(0));
+
+        let named_socket_addr = NamedSocketAddr::from((test_util::LOCALHOST, local_addr.port().unwrap()));
+        let (client, accepted) = tokio::join!(Stream::connect(&named_socket_addr), listener.accept());
+
+        assert_eq!(accepted.unwrap().1, client.unwrap().local_addr().unwrap());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn bind_unix_mkdir() {
+        let dir = test_util::temp_path("mkdir");
+        let path = dir.join("nested").join("app.sock");
+        let listener = Listener::bind_unix_mkdir(&NamedSocketAddr::unix(&path), Some(0o700)).await.unwrap();
+
+        assert_eq!(fs::metadata(dir.join("nested")).unwrap().permissions().mode() & 0o777, 0o700);
+        test_util::connect_to(&listener).await;
+        listener.close().unwrap();
+
+        // NOTE: Existing directories keep their mode.
+        let listener = Listener::bind_unix_mkdir(&NamedSocketAddr::unix(&path), Some(0o755)).await.unwrap();
+
+        assert_eq!(fs::metadata(dir.join("nested")).unwrap().permissions().mode() & 0o777, 0o700);
+        listener.close().unwrap();
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        let tcp_listener = Listener::bind_unix_mkdir(&NamedSocketAddr::from((test_util::LOCALHOST, 0)), None).await.unwrap();
+
+        assert_eq!(tcp_listener.transport(), Transport::Tcp);
+    }
+
+    #[tokio::test]
+    async fn accept_with() {
+        use tokio::io::AsyncReadExt;
+
+        let (listener, local_addr) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+        let named_socket_addr = local_addr.to_named_socket_addr().unwrap();
+
+        let client = Stream::connect(&named_socket_addr).await.unwrap();
+        let (stream, _) = listener.accept_with(|stream| stream.set_recv_buffer_size(32768)).await.unwrap();
+
+        assert!(stream.recv_buffer_size().unwrap() >= 32768);
+        drop(client);
+
+        // NOTE: The stream is closed if configuring it fails.
+        let mut client = Stream::connect(&named_socket_addr).await.unwrap();
+        let error = listener.accept_with(|_| Err(io::Error::new(io::ErrorKind::InvalidInput, "configure"))).await.unwrap_err();
+
+        assert_eq!(error.to_string(), "configure");
+        assert_eq!(client.read(&mut [0; 1]).await.unwrap(), 0);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn close() {
+        let path = test_util::temp_path("close.sock");
+        let named_socket_addr = NamedSocketAddr::unix(&path);
+        let listener = Listener::bind(&named_socket_addr).await.unwrap();
+
+        listener.close().unwrap();
+
+        assert!(!path.exists());
+        assert!(Stream::connect(&named_socket_addr).await.is_err());
+
+        // NOTE: A file replacing the socket isn't removed.
+        let listener = Listener::bind(&named_socket_addr).await.unwrap();
+
+        fs::remove_file(&path).unwrap();
+        fs::write(&path, b"").unwrap();
+        listener.close().unwrap();
+
+        assert!(path.exists());
+        fs::remove_file(&path).unwrap();
+
+        let (tcp_listener, local_addr) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+
+        tcp_listener.close().unwrap();
+        assert!(Stream::connect(&local_addr.to_named_socket_addr().unwrap()).await.is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn bind_with_tos() {
+        let listener = Listener::bind_with_tos(&NamedSocketAddr::from((test_util::LOCALHOST, 0)), 0x28).await.unwrap();
+        let (client, server) = test_util::connect_to(&listener).await;
+
+        #[cfg(target_os = "linux")]
+        assert_eq!(server.tos().unwrap(), 0x28);
+        #[cfg(not(target_os = "linux"))]
+        drop(server);
+
+        client.set_tos(0xb8).unwrap();
+        assert_eq!(client.tos().unwrap(), 0xb8);
+
+        // NOTE: IPv6 uses the traffic class instead, if IPv6 is available at all.
+        if let Ok(listener) = Listener::bind_with_tos(&NamedSocketAddr::from((net::Ipv6Addr::LOCALHOST, 0)), 0x28).await {
+            let (client, _server) = test_util::connect_to(&listener).await;
+
+            client.set_tos(0xb8).unwrap();
+            assert_eq!(client.tos().unwrap(), 0xb8);
+        }
+
+        let unix_socket_addr = NamedSocketAddr::unix(test_util::temp_path("tos.sock"));
+
+        assert_eq!(Listener::bind_with_tos(&unix_socket_addr, 0x28).await.unwrap_err().kind(), io::ErrorKind::Unsupported);
+        assert_eq!(test_util::unix_pair().0.set_tos(0x28).unwrap_err().kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[tokio::test]
+    async fn accept_with_backoff() {
+        let (listener, local_addr) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+        let named_socket_addr = local_addr.to_named_socket_addr().unwrap();
+
+        let (client, accepted) = tokio::join!(Stream::connect(&named_socket_addr), listener.accept_with_backoff(Duration::from_millis(1)));
+
+        assert_eq!(accepted.unwrap().1, client.unwrap().local_addr().unwrap());
+
+        // NOTE: Shutting down a listening socket makes accept fail with EINVAL on Linux,
+        //       which isn't retried.
+        #[cfg(target_os = "linux")]
+        {
+            assert_eq!(unsafe { libc::shutdown(listener.as_raw_fd(), libc::SHUT_RD) }, 0);
+
+            let error = time::timeout(Duration::from_secs(5), listener.accept_with_backoff(Duration::from_millis(1))).await.unwrap().unwrap_err();
+
+            assert_eq!(error.raw_os_error(), Some(libc::EINVAL));
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn rebind() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let old_path = test_util::temp_path("rebind-old.sock");
+        let new_path = test_util::temp_path("rebind-new.sock");
+        let new_addr = NamedSocketAddr::unix(&new_path);
+        let mut listener = Listener::bind(&NamedSocketAddr::unix(&old_path)).await.unwrap();
+
+        listener.rebind(&new_addr, false, Some(0o600)).await.unwrap();
+
+        assert!(!old_path.exists());
+        assert_eq!(fs::metadata(&new_path).unwrap().permissions().mode() & 0o777, 0o600);
+
+        let (client, accepted) = tokio::join!(Stream::connect(&new_addr), listener.accept());
+
+        client.unwrap();
+        accepted.unwrap();
+
+        // NOTE: Rebinding to the same path keeps the new socket file.
+        listener.rebind(&new_addr, true, None).await.unwrap();
+
+        assert!(new_path.exists());
+
+        let (client, accepted) = tokio::join!(Stream::connect(&new_addr), listener.accept());
+
+        client.unwrap();
+        accepted.unwrap();
+
+        // NOTE: The same path spelled relative to the working directory is still the same path.
+        let current_dir = std::env::current_dir().unwrap();
+        let relative_path = current_dir
+            .components()
+            .skip(1)
+            .map(|_| Path::new(".."))
+            .collect::<std::path::PathBuf>()
+            .join(new_path.strip_prefix("/").unwrap());
+
+        assert!(relative_path.is_relative());
+
+        listener.rebind(&NamedSocketAddr::unix(&relative_path), true, None).await.unwrap();
+
+        assert!(new_path.exists());
+
+        let (client, accepted) = tokio::join!(Stream::connect(&new_addr), listener.accept());
+
+        client.unwrap();
+        accepted.unwrap();
+
+        // NOTE: The old listener is kept if binding fails.
+        let blocker = Listener::bind(&NamedSocketAddr::unix(&old_path)).await.unwrap();
+
+        assert!(listener.rebind(&NamedSocketAddr::unix(&old_path), false, None).await.is_err());
+        assert_eq!(listener.local_addr().unwrap().as_unix().and_then(UnixSocketAddr::as_pathname), Some(relative_path.as_path()));
+
+        blocker.close().unwrap();
+        listener.close().unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn unix_flags_set_atomically() {
+        use std::os::linux::net::SocketAddrExt;
+
+        use crate::StdStream;
+
+        let path = test_util::temp_path("flags.sock");
+        let listener = Listener::bind(&NamedSocketAddr::unix(&path)).await.unwrap();
+        let (_client, server) = test_util::connect_to(&listener).await;
+
+        assert!(test_util::is_cloexec(listener.as_raw_fd()) && test_util::is_nonblocking(listener.as_raw_fd()));
+        assert!(test_util::is_cloexec(server.as_raw_fd()) && test_util::is_nonblocking(server.as_raw_fd()));
+
+        listener.close().unwrap();
+
+        let name = format!("tokio-unix-tcp-{}-flags", std::process::id()).into_bytes();
+        let listener = Listener::bind_bindable(&BindableAddr::Abstract(name.clone())).await.unwrap();
+
+        let abstract_addr = unix::net::SocketAddr::from_abstract_name(&name).unwrap();
+        let _client = Stream::from_std(StdStream::Unix(unix::net::UnixStream::connect_addr(&abstract_addr).unwrap())).unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+
+        assert!(test_util::is_cloexec(listener.as_raw_fd()) && test_util::is_nonblocking(listener.as_raw_fd()));
+        assert!(test_util::is_cloexec(server.as_raw_fd()) && test_util::is_nonblocking(server.as_raw_fd()));
+    }
 }
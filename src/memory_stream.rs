@@ -0,0 +1,114 @@
+/*
+ * Copyright (c) 2023, networkException <git@nwex.de>
+ *
+ * SPDX-License-Identifier: BSD-2-Clause OR MIT
+ */
+
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncWrite, DuplexStream, ReadBuf};
+
+// NOTE: An in-memory pipe for testing protocol code deterministically without touching the OS.
+//       It's a separate type rather than a variant of Stream since it has no address, socket
+//       options or file descriptor, so code under test should be generic over AsyncRead and
+//       AsyncWrite to accept both.
+#[derive(Debug)]
+pub struct MemoryStream {
+    inner: DuplexStream,
+}
+
+impl MemoryStream {
+    // Creates two streams connected to each other. Writes wait once capacity bytes are
+    // buffered and haven't been read by the other side.
+    pub fn pair(capacity: usize) -> (MemoryStream, MemoryStream) {
+        let (a, b) = tokio::io::duplex(capacity);
+
+        (MemoryStream { inner: a }, MemoryStream { inner: b })
+    }
+
+    pub fn into_inner(self) -> DuplexStream {
+        self.inner
+    }
+}
+
+impl From<DuplexStream> for MemoryStream {
+    fn from(duplex_stream: DuplexStream) -> MemoryStream {
+        MemoryStream { inner: duplex_stream }
+    }
+}
+
+impl AsyncRead for MemoryStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut Pin::into_inner(self).inner).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for MemoryStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut Pin::into_inner(self).inner).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut Pin::into_inner(self).inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut Pin::into_inner(self).inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut Pin::into_inner(self).inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn pair() {
+        let (mut a, mut b) = MemoryStream::pair(4);
+
+        // NOTE: Writes wait once capacity bytes are buffered.
+        let (written, read) = tokio::join!(a.write_all(b"hello world"), async {
+            let mut buf = Vec::new();
+
+            while buf.len() < 11 {
+                let mut chunk = [0; 4];
+                let size = b.read(&mut chunk).await.unwrap();
+
+                assert!(size <= 4);
+                buf.extend_from_slice(&chunk[..size]);
+            }
+
+            buf
+        });
+
+        written.unwrap();
+        assert_eq!(read, b"hello world");
+
+        a.shutdown().await.unwrap();
+
+        assert_eq!(b.read(&mut [0; 1]).await.unwrap(), 0);
+    }
+}
@@ -0,0 +1,128 @@
+/*
+ * Copyright (c) 2023, networkException <git@nwex.de>
+ *
+ * SPDX-License-Identifier: BSD-2-Clause OR MIT
+ */
+
+use std::future;
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::task::Poll;
+
+use crate::{Listener, NamedSocketAddr, SocketAddr, Stream};
+
+// NOTE: Accepting starts polling at a different listener every time so a busy
+//       listener can't starve the others. As named pipe listeners can't be polled
+//       for connections, they are not supported.
+// NOTE: Accepting polls the listeners, so only one task should accept at a time,
+//       see Listener::poll_accept.
+#[derive(Debug)]
+pub struct MultiListener {
+    listeners: Vec<Listener>,
+    next: AtomicUsize,
+}
+
+impl MultiListener {
+    pub fn new(listeners: Vec<Listener>) -> MultiListener {
+        MultiListener {
+            listeners,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    pub async fn bind_all(named_socket_addrs: &[NamedSocketAddr]) -> io::Result<MultiListener> {
+        let mut listeners = Vec::with_capacity(named_socket_addrs.len());
+
+        for named_socket_addr in named_socket_addrs {
+            listeners.push(Listener::bind(named_socket_addr).await?);
+        }
+
+        Ok(MultiListener::new(listeners))
+    }
+
+    pub fn listeners(&self) -> &[Listener] {
+        &self.listeners
+    }
+
+    pub fn into_listeners(self) -> Vec<Listener> {
+        self.listeners
+    }
+
+    pub fn local_addrs(&self) -> io::Result<Vec<SocketAddr>> {
+        self.listeners.iter().map(Listener::local_addr).collect()
+    }
+
+    pub async fn accept(&self) -> io::Result<(Stream, SocketAddr)> {
+        if self.listeners.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Cannot accept without any listener.",
+            ))
+        }
+
+        // NOTE: fetch_add wraps around on overflow, reducing start keeps start + offset in range.
+        let start = self.next.fetch_add(1, Ordering::Relaxed) % self.listeners.len();
+
+        future::poll_fn(|cx| {
+            for offset in 0..self.listeners.len() {
+                let listener = &self.listeners[(start + offset) % self.listeners.len()];
+
+                if let Poll::Ready(result) = listener.poll_accept(cx) {
+                    return Poll::Ready(result)
+                }
+            }
+
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+impl From<Vec<Listener>> for MultiListener {
+    fn from(listeners: Vec<Listener>) -> MultiListener {
+        MultiListener::new(listeners)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(unix)]
+    use crate::test_util;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn accepts_tcp_and_unix() {
+        let path = test_util::temp_path("multi.sock");
+        let named_socket_addrs = [NamedSocketAddr::Inet((test_util::LOCALHOST, 0).into()), NamedSocketAddr::unix(&path)];
+        let multi_listener = MultiListener::bind_all(&named_socket_addrs).await.unwrap();
+
+        let local_addrs = multi_listener.local_addrs().unwrap();
+
+        assert_eq!(local_addrs.len(), 2);
+        assert_eq!(local_addrs[1], SocketAddr::unix(&path));
+
+        for local_addr in local_addrs {
+            let named_socket_addr = local_addr.to_named_socket_addr().unwrap();
+            let (client, accepted) = tokio::join!(Stream::connect(&named_socket_addr), multi_listener.accept());
+            let (client, (stream, peer_addr)) = (client.unwrap(), accepted.unwrap());
+
+            assert_eq!(stream.transport(), client.transport());
+            assert_eq!(stream.local_addr().unwrap(), client.peer_addr().unwrap());
+            assert_eq!(peer_addr, client.local_addr().unwrap());
+        }
+
+        for listener in multi_listener.into_listeners() {
+            listener.close().unwrap();
+        }
+
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn accept_without_listeners() {
+        let error = MultiListener::new(Vec::new()).accept().await.unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+}
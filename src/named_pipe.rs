@@ -0,0 +1,110 @@
+/*
+ * Copyright (c) 2023, networkException <git@nwex.de>
+ *
+ * SPDX-License-Identifier: BSD-2-Clause OR MIT
+ */
+
+use std::io;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use tokio::net::windows::named_pipe::{ClientOptions, NamedPipeClient, NamedPipeServer, ServerOptions};
+use tokio::sync::Mutex;
+use tokio::time;
+
+// NOTE: Returned by the system when all instances of a pipe are busy.
+const ERROR_PIPE_BUSY: i32 = 231;
+
+// A named pipe server instance can only serve a single client. To resemble a
+// listener, the next instance is always created before the current one is handed
+// out to a client.
+#[derive(Debug)]
+pub struct NamedPipeListener {
+    pipe_name: PathBuf,
+    next: Mutex<NamedPipeServer>,
+}
+
+impl NamedPipeListener {
+    pub fn bind(pipe_name: &Path) -> io::Result<NamedPipeListener> {
+        let next = ServerOptions::new().first_pipe_instance(true).create(pipe_name)?;
+
+        Ok(NamedPipeListener {
+            pipe_name: pipe_name.to_path_buf(),
+            next: Mutex::new(next),
+        })
+    }
+
+    pub fn pipe_name(&self) -> &Path {
+        &self.pipe_name
+    }
+
+    // NOTE: The instance is only replaced once a client connected to it (or connecting failed),
+    //       so cancelling accept doesn't drop a client that connected in the meantime. Concurrent
+    //       calls wait for each other.
+    pub async fn accept(&self) -> io::Result<NamedPipeServer> {
+        let mut next = self.next.lock().await;
+        let connected = next.connect().await;
+        let server = mem::replace(&mut *next, ServerOptions::new().create(&self.pipe_name)?);
+
+        connected.map(|()| server)
+    }
+}
+
+pub(crate) async fn connect(pipe_name: &Path) -> io::Result<NamedPipeClient> {
+    loop {
+        match ClientOptions::new().open(pipe_name) {
+            Err(error) if error.raw_os_error() == Some(ERROR_PIPE_BUSY) => {}
+            result => return result,
+        }
+
+        time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+    use crate::{Listener, NamedSocketAddr, Stream};
+
+    fn pipe_name(name: &str) -> NamedSocketAddr {
+        format!(r"\\.\pipe\tokio-unix-tcp-{}-{}", std::process::id(), name).parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn connect_and_accept() {
+        let named_socket_addr = pipe_name("accept");
+        let listener = Listener::bind(&named_socket_addr).await.unwrap();
+
+        for _ in 0..2 {
+            let (client, accepted) = tokio::join!(Stream::connect(&named_socket_addr), listener.accept());
+            let (mut client, mut server) = (client.unwrap(), accepted.unwrap().0);
+
+            client.write_all(b"ping").await.unwrap();
+
+            let mut buf = [0; 4];
+            server.read_exact(&mut buf).await.unwrap();
+            assert_eq!(&buf, b"ping");
+        }
+    }
+
+    #[tokio::test]
+    async fn cancelled_accept_keeps_the_instance() {
+        let named_socket_addr = pipe_name("cancel");
+        let listener = Listener::bind(&named_socket_addr).await.unwrap();
+
+        assert!(time::timeout(Duration::from_millis(10), listener.accept()).await.is_err());
+
+        // NOTE: Connects to the instance the cancelled accept was waiting on.
+        let mut client = Stream::connect(&named_socket_addr).await.unwrap();
+        let (mut server, _) = time::timeout(Duration::from_secs(5), listener.accept()).await.unwrap().unwrap();
+
+        client.write_all(b"ping").await.unwrap();
+
+        let mut buf = [0; 4];
+        server.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ping");
+    }
+}
@@ -0,0 +1,262 @@
+/*
+ * Copyright (c) 2023, networkException <git@nwex.de>
+ *
+ * SPDX-License-Identifier: BSD-2-Clause OR MIT
+ */
+
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::io;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex, Once, Weak};
+use std::time::{Duration, Instant};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time;
+
+use crate::{NamedSocketAddr, Stream};
+
+struct Entry {
+    idle: Vec<(Stream, Instant)>,
+    semaphore: Arc<Semaphore>,
+}
+
+struct Inner {
+    max_per_addr: usize,
+    idle_timeout: Duration,
+    entries: Mutex<HashMap<NamedSocketAddr, Entry>>,
+    reaper: Once,
+}
+
+// NOTE: At most max_per_addr connections to an address are checked out at the same time,
+//       further calls to get wait until one is returned. Idle connections are closed by a
+//       background task once they haven't been used for idle_timeout, which is spawned on
+//       the runtime of the first call to get.
+#[derive(Clone)]
+pub struct Pool {
+    inner: Arc<Inner>,
+}
+
+// NOTE: Returned to the pool when dropped, so it should only be dropped while the protocol
+//       is in a state where the next request can be sent. Use into_inner otherwise.
+pub struct PooledStream {
+    stream: Option<Stream>,
+    named_socket_addr: NamedSocketAddr,
+    inner: Arc<Inner>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Pool {
+    // Fails with io::ErrorKind::InvalidInput if max_per_addr or idle_timeout is zero, which
+    // would make get wait forever or the background task spin.
+    pub fn new(max_per_addr: usize, idle_timeout: Duration) -> io::Result<Pool> {
+        if max_per_addr == 0 || idle_timeout.is_zero() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Cannot create a pool without any connections per address or without an idle timeout.",
+            ))
+        }
+
+        Ok(Pool {
+            inner: Arc::new(Inner {
+                max_per_addr,
+                idle_timeout,
+                entries: Mutex::new(HashMap::new()),
+                reaper: Once::new(),
+            }),
+        })
+    }
+
+    // Hands out an idle connection to the address if there is one that the peer didn't
+    // close yet, or connects a new one.
+    pub async fn get(&self, named_socket_addr: &NamedSocketAddr) -> io::Result<PooledStream> {
+        self.inner.reaper.call_once(|| {
+            tokio::spawn(reap(Arc::downgrade(&self.inner)));
+        });
+
+        let semaphore = self.inner.entry(named_socket_addr, |entry| entry.semaphore.clone());
+        let permit = semaphore.acquire_owned().await.expect("Pool semaphores are never closed");
+
+        let stream = loop {
+            match self.inner.entry(named_socket_addr, |entry| entry.idle.pop()) {
+                Some((stream, _)) if matches!(stream.is_read_closed(), Ok(false)) => break stream,
+                Some(_) => continue,
+                None => break Stream::connect(named_socket_addr).await?,
+            }
+        };
+
+        Ok(PooledStream {
+            stream: Some(stream),
+            named_socket_addr: named_socket_addr.clone(),
+            inner: self.inner.clone(),
+            _permit: permit,
+        })
+    }
+
+    pub fn idle_count(&self, named_socket_addr: &NamedSocketAddr) -> usize {
+        let entries = self.inner.entries.lock().unwrap();
+
+        entries.get(named_socket_addr).map_or(0, |entry| entry.idle.len())
+    }
+}
+
+impl Inner {
+    fn entry<R>(&self, named_socket_addr: &NamedSocketAddr, f: impl FnOnce(&mut Entry) -> R) -> R {
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries.entry(named_socket_addr.clone()).or_insert_with(|| Entry {
+            idle: Vec::new(),
+            semaphore: Arc::new(Semaphore::new(self.max_per_addr)),
+        });
+
+        f(entry)
+    }
+
+    // Closes connections that were idle for longer than idle_timeout and forgets
+    // addresses without any connections.
+    fn reap(&self) {
+        let mut entries = self.entries.lock().unwrap();
+
+        entries.retain(|_, entry| {
+            entry.idle.retain(|(_, idle_since)| idle_since.elapsed() < self.idle_timeout);
+
+            !entry.idle.is_empty() || Arc::strong_count(&entry.semaphore) > 1
+        });
+    }
+}
+
+async fn reap(inner: Weak<Inner>) {
+    loop {
+        let Some(idle_timeout) = inner.upgrade().map(|inner| inner.idle_timeout) else {
+            return
+        };
+
+        time::sleep(idle_timeout).await;
+
+        match inner.upgrade() {
+            Some(inner) => inner.reap(),
+            None => return,
+        }
+    }
+}
+
+impl PooledStream {
+    pub fn named_socket_addr(&self) -> &NamedSocketAddr {
+        &self.named_socket_addr
+    }
+
+    // Takes the stream out of the pool, it won't be returned when dropped.
+    pub fn into_inner(mut self) -> Stream {
+        self.stream.take().unwrap()
+    }
+}
+
+impl Deref for PooledStream {
+    type Target = Stream;
+
+    fn deref(&self) -> &Stream {
+        self.stream.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for PooledStream {
+    fn deref_mut(&mut self) -> &mut Stream {
+        self.stream.as_mut().unwrap()
+    }
+}
+
+impl Drop for PooledStream {
+    fn drop(&mut self) {
+        if let Some(stream) = self.stream.take() {
+            self.inner.entry(&self.named_socket_addr, |entry| entry.idle.push((stream, Instant::now())));
+        }
+    }
+}
+
+impl Debug for Pool {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pool")
+            .field("max_per_addr", &self.inner.max_per_addr)
+            .field("idle_timeout", &self.inner.idle_timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Debug for PooledStream {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PooledStream")
+            .field("named_socket_addr", &self.named_socket_addr)
+            .field("stream", &self.stream)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_util, Listener};
+
+    #[tokio::test]
+    async fn reuses_idle_connections() {
+        let (listener, local_addr) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+        let named_socket_addr = local_addr.to_named_socket_addr().unwrap();
+        let pool = Pool::new(1, Duration::from_secs(60)).unwrap();
+
+        let (pooled_stream, accepted) = tokio::join!(pool.get(&named_socket_addr), listener.accept());
+        let (pooled_stream, (server, _)) = (pooled_stream.unwrap(), accepted.unwrap());
+        let client_addr = pooled_stream.local_addr().unwrap();
+
+        // NOTE: Only one connection may be checked out at a time.
+        assert!(time::timeout(Duration::from_millis(10), pool.get(&named_socket_addr)).await.is_err());
+
+        drop(pooled_stream);
+        assert_eq!(pool.idle_count(&named_socket_addr), 1);
+
+        let pooled_stream = pool.get(&named_socket_addr).await.unwrap();
+
+        assert_eq!(pooled_stream.local_addr().unwrap(), client_addr);
+        assert_eq!(pool.idle_count(&named_socket_addr), 0);
+
+        // NOTE: Connections the peer closed while idle are replaced.
+        drop(pooled_stream);
+        drop(server);
+        time::sleep(Duration::from_millis(10)).await;
+
+        let (pooled_stream, accepted) = tokio::join!(pool.get(&named_socket_addr), listener.accept());
+
+        assert_ne!(pooled_stream.unwrap().local_addr().unwrap(), client_addr);
+        accepted.unwrap();
+    }
+
+    #[tokio::test]
+    async fn closes_idle_connections() {
+        let (listener, local_addr) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+        let named_socket_addr = local_addr.to_named_socket_addr().unwrap();
+        let pool = Pool::new(2, Duration::from_millis(20)).unwrap();
+
+        let (pooled_stream, accepted) = tokio::join!(pool.get(&named_socket_addr), listener.accept());
+        accepted.unwrap();
+
+        let stream = pooled_stream.unwrap().into_inner();
+        assert_eq!(pool.idle_count(&named_socket_addr), 0);
+
+        pool.inner.entry(&named_socket_addr, |entry| entry.idle.push((stream, Instant::now())));
+        assert_eq!(pool.idle_count(&named_socket_addr), 1);
+
+        time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(pool.idle_count(&named_socket_addr), 0);
+    }
+
+    #[tokio::test]
+    async fn errors() {
+        assert_eq!(Pool::new(0, Duration::from_secs(1)).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(Pool::new(1, Duration::ZERO).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+
+        let pool = Pool::new(1, Duration::from_secs(60)).unwrap();
+        let named_socket_addr = test_util::unused_tcp_addr().await;
+
+        assert_eq!(pool.get(&named_socket_addr).await.unwrap_err().kind(), io::ErrorKind::ConnectionRefused);
+
+        // NOTE: A failed connect doesn't leak the permit.
+        assert_eq!(pool.get(&named_socket_addr).await.unwrap_err().kind(), io::ErrorKind::ConnectionRefused);
+    }
+}
@@ -0,0 +1,274 @@
+/*
+ * Copyright (c) 2023, networkException <git@nwex.de>
+ *
+ * SPDX-License-Identifier: BSD-2-Clause OR MIT
+ */
+
+use std::io;
+use std::net::{self, IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use tokio::io::AsyncReadExt;
+use tokio::time;
+
+use crate::{Listener, SocketAddr, Stream, UnixSocketAddr};
+
+const V1_PREFIX: &[u8] = b"PROXY ";
+// NOTE: Including the terminating CRLF, see section 2.1 of the specification.
+const V1_MAX_LENGTH: usize = 107;
+const V2_SIGNATURE: &[u8] = b"\r\n\r\n\0\r\nQUIT\n";
+const V2_HEADER_LENGTH: usize = 16;
+const V2_UNIX_ADDR_LENGTH: usize = 108;
+
+impl Listener {
+    // Accepts a connection and reads a PROXY protocol (version 1 or 2) header from it,
+    // returning the source address relayed by the proxy next to the direct peer address.
+    // The header is consumed from the stream. The source address is None for LOCAL or
+    // UNKNOWN connections, which are for example used by health checks.
+    // NOTE: The header is read before accepting the next connection, use accept and
+    //       Stream::read_proxy_protocol in a spawned task to not wait on slow clients.
+    pub async fn accept_proxy_protocol(&self, header_timeout: Duration) -> io::Result<(Stream, SocketAddr, Option<SocketAddr>)> {
+        let (mut stream, socket_addr) = self.accept().await?;
+        let source = stream.read_proxy_protocol(header_timeout).await?;
+
+        Ok((stream, socket_addr, source))
+    }
+}
+
+impl Stream {
+    // Reads a PROXY protocol header from the stream, see Listener::accept_proxy_protocol.
+    // Fails with io::ErrorKind::TimedOut if the header wasn't read before the timeout elapsed.
+    pub async fn read_proxy_protocol(&mut self, timeout: Duration) -> io::Result<Option<SocketAddr>> {
+        match time::timeout(timeout, read_header(self)).await {
+            Ok(result) => result,
+            Err(_elapsed) => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "Cannot read the proxy protocol header before the timeout elapsed.",
+            )),
+        }
+    }
+}
+
+// NOTE: Reads at most the length of the header so the payload following it stays
+//       in the stream. Both versions can be told apart by their first 8 bytes.
+async fn read_header(stream: &mut Stream) -> io::Result<Option<SocketAddr>> {
+    let mut header = vec![0; 8];
+
+    stream.read_exact(&mut header).await?;
+
+    if header.starts_with(V1_PREFIX) {
+        while !header.ends_with(b"\r\n") {
+            if header.len() == V1_MAX_LENGTH {
+                return Err(invalid_header("version 1 header is too long"))
+            }
+
+            header.push(stream.read_u8().await?);
+        }
+
+        return parse_v1(&header[V1_PREFIX.len()..header.len() - 2])
+    }
+
+    if !V2_SIGNATURE.starts_with(&header) {
+        return Err(invalid_header("missing signature"))
+    }
+
+    header.resize(V2_HEADER_LENGTH, 0);
+    stream.read_exact(&mut header[8..]).await?;
+
+    if header[..V2_SIGNATURE.len()] != *V2_SIGNATURE {
+        return Err(invalid_header("missing signature"))
+    }
+
+    let mut addresses = vec![0; u16::from_be_bytes([header[14], header[15]]) as usize];
+
+    stream.read_exact(&mut addresses).await?;
+
+    parse_v2(header[12], header[13], &addresses)
+}
+
+fn parse_v1(line: &[u8]) -> io::Result<Option<SocketAddr>> {
+    let line = std::str::from_utf8(line).map_err(|_| invalid_header("version 1 header is not valid ascii"))?;
+    let fields = line.split(' ').collect::<Vec<_>>();
+
+    let ip = match fields[..] {
+        ["UNKNOWN", ..] => return Ok(None),
+        ["TCP4", source, _, _, _] => source.parse::<Ipv4Addr>().map(IpAddr::V4),
+        ["TCP6", source, _, _, _] => source.parse::<Ipv6Addr>().map(IpAddr::V6),
+        _ => return Err(invalid_header("unknown version 1 protocol")),
+    };
+
+    let ip = ip.map_err(|_| invalid_header("invalid version 1 source address"))?;
+    let port = fields[3].parse().map_err(|_| invalid_header("invalid version 1 source port"))?;
+
+    Ok(Some(SocketAddr::Inet(net::SocketAddr::new(ip, port))))
+}
+
+fn parse_v2(version_command: u8, family_protocol: u8, addresses: &[u8]) -> io::Result<Option<SocketAddr>> {
+    if version_command >> 4 != 2 {
+        return Err(invalid_header("unsupported version"))
+    }
+
+    match version_command & 0x0f {
+        0x0 => return Ok(None),
+        0x1 => {}
+        _ => return Err(invalid_header("unknown version 2 command")),
+    }
+
+    let (ip, port) = match family_protocol >> 4 {
+        0x0 => return Ok(None),
+        0x1 if addresses.len() >= 12 => {
+            let ip = <[u8; 4]>::try_from(&addresses[0..4]).unwrap();
+
+            (IpAddr::from(ip), &addresses[8..10])
+        }
+        0x2 if addresses.len() >= 36 => {
+            let ip = <[u8; 16]>::try_from(&addresses[0..16]).unwrap();
+
+            (IpAddr::from(ip), &addresses[32..34])
+        }
+        0x3 if addresses.len() >= 2 * V2_UNIX_ADDR_LENGTH => {
+            return Ok(Some(SocketAddr::Unix(parse_v2_unix(&addresses[..V2_UNIX_ADDR_LENGTH]))))
+        }
+        0x1..=0x3 => return Err(invalid_header("version 2 addresses are too short")),
+        _ => return Err(invalid_header("unknown version 2 address family")),
+    };
+
+    Ok(Some(SocketAddr::Inet(net::SocketAddr::new(ip, u16::from_be_bytes([port[0], port[1]])))))
+}
+
+fn parse_v2_unix(address: &[u8]) -> UnixSocketAddr {
+    let name = match address.iter().rposition(|byte| *byte != 0) {
+        Some(end) => &address[..=end],
+        None => return UnixSocketAddr::Unnamed,
+    };
+
+    match name.strip_prefix(&[0]) {
+        Some(abstract_name) => UnixSocketAddr::Abstract(abstract_name.to_vec()),
+        None => UnixSocketAddr::Pathname(PathBuf::from(String::from_utf8_lossy(name).into_owned())),
+    }
+}
+
+fn invalid_header(reason: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Cannot parse proxy protocol header: {}.", reason),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::AsyncWriteExt;
+
+    use super::*;
+    use crate::test_util;
+
+    async fn read_from(header: &[u8]) -> (io::Result<Option<SocketAddr>>, Stream) {
+        let (mut client, mut server) = test_util::tcp_pair().await;
+
+        client.write_all(header).await.unwrap();
+        client.write_all(b"payload").await.unwrap();
+
+        (server.read_proxy_protocol(Duration::from_secs(5)).await, server)
+    }
+
+    fn v2_header(command: u8, family_protocol: u8, addresses: &[u8]) -> Vec<u8> {
+        let mut header = V2_SIGNATURE.to_vec();
+
+        header.extend([0x20 | command, family_protocol]);
+        header.extend((addresses.len() as u16).to_be_bytes());
+        header.extend(addresses);
+        header
+    }
+
+    #[tokio::test]
+    async fn version_1() {
+        let (source, mut stream) = read_from(b"PROXY TCP4 192.0.2.1 198.51.100.1 56324 443\r\n").await;
+
+        assert_eq!(source.unwrap(), Some("192.0.2.1:56324".parse().unwrap()));
+
+        // NOTE: The payload following the header stays in the stream.
+        let mut payload = [0; 7];
+        stream.read_exact(&mut payload).await.unwrap();
+        assert_eq!(&payload, b"payload");
+
+        let (source, _) = read_from(b"PROXY TCP6 2001:db8::1 2001:db8::2 4000 443\r\n").await;
+
+        assert_eq!(source.unwrap(), Some("[2001:db8::1]:4000".parse().unwrap()));
+
+        let (source, _) = read_from(b"PROXY UNKNOWN\r\n").await;
+
+        assert_eq!(source.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn version_2() {
+        let mut inet6 = Ipv6Addr::LOCALHOST.octets().to_vec();
+
+        inet6.extend(Ipv6Addr::UNSPECIFIED.octets());
+        inet6.extend(8080u16.to_be_bytes());
+        inet6.extend(443u16.to_be_bytes());
+
+        let (source, mut stream) = read_from(&v2_header(0x1, 0x21, &inet6)).await;
+
+        assert_eq!(source.unwrap(), Some("[::1]:8080".parse().unwrap()));
+
+        let mut payload = [0; 7];
+        stream.read_exact(&mut payload).await.unwrap();
+        assert_eq!(&payload, b"payload");
+
+        let mut unix = vec![0; 2 * V2_UNIX_ADDR_LENGTH];
+
+        unix[..13].copy_from_slice(b"/run/app.sock");
+
+        let (source, _) = read_from(&v2_header(0x1, 0x31, &unix)).await;
+
+        assert_eq!(source.unwrap(), Some(SocketAddr::unix("/run/app.sock")));
+
+        // NOTE: LOCAL connections, e.g. health checks of the proxy, carry no source.
+        let (source, _) = read_from(&v2_header(0x0, 0x00, &[])).await;
+
+        assert_eq!(source.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn invalid_headers() {
+        let too_long = [V1_PREFIX, &[b'0'; V1_MAX_LENGTH]].concat();
+
+        let headers = [
+            &b"GET / HTTP/1.1\r\n"[..],
+            b"PROXY TCP4 192.0.2.1 198.51.100.1 port 443\r\n",
+            b"PROXY UDP4 192.0.2.1 198.51.100.1 56324 443\r\n",
+            &too_long,
+            &v2_header(0x1, 0x11, &[0; 4]),
+            &v2_header(0x2, 0x11, &[0; 12]),
+        ];
+
+        for header in headers {
+            let (source, _) = read_from(header).await;
+
+            assert_eq!(source.unwrap_err().kind(), io::ErrorKind::InvalidData, "{}", header.escape_ascii());
+        }
+    }
+
+    #[tokio::test]
+    async fn accept_proxy_protocol() {
+        let (listener, local_addr) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+        let mut client = Stream::connect(&local_addr.to_named_socket_addr().unwrap()).await.unwrap();
+
+        client.write_all(b"PROXY TCP4 192.0.2.1 198.51.100.1 56324 443\r\n").await.unwrap();
+
+        let (_, peer_addr, source) = listener.accept_proxy_protocol(Duration::from_secs(5)).await.unwrap();
+
+        assert_eq!(peer_addr, client.local_addr().unwrap());
+        assert_eq!(source, Some("192.0.2.1:56324".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn header_timeout() {
+        let (_client, mut server) = test_util::tcp_pair().await;
+        let error = server.read_proxy_protocol(Duration::from_millis(10)).await.unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+    }
+}
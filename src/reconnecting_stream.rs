@@ -0,0 +1,250 @@
+/*
+ * Copyright (c) 2023, networkException <git@nwex.de>
+ *
+ * SPDX-License-Identifier: BSD-2-Clause OR MIT
+ */
+
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::{NamedSocketAddr, Stream};
+
+type ConnectFuture = Pin<Box<dyn Future<Output = io::Result<Stream>> + Send>>;
+type Handshake = Arc<dyn Fn(Stream) -> ConnectFuture + Send + Sync>;
+
+enum State {
+    Disconnected,
+    Connecting(ConnectFuture),
+    Connected(Stream),
+}
+
+// NOTE: Reconnecting in the middle of a stream loses anything the peer has not
+//       processed yet and any protocol state tied to the old connection. This is
+//       only safe for stateless or idempotent framing, use with_handshake to
+//       re-establish session state on every new connection.
+pub struct ReconnectingStream {
+    named_socket_addr: NamedSocketAddr,
+    retries: usize,
+    initial_backoff: Duration,
+    handshake: Option<Handshake>,
+    state: State,
+    reconnects: usize,
+}
+
+impl ReconnectingStream {
+    // Connects lazily on first use. Every reconnect uses Stream::connect_retry with
+    // the given retries and backoff, at most retries reconnects are attempted in a
+    // row without a successful operation in between.
+    pub fn new(named_socket_addr: NamedSocketAddr, retries: usize, initial_backoff: Duration) -> ReconnectingStream {
+        ReconnectingStream {
+            named_socket_addr,
+            retries,
+            initial_backoff,
+            handshake: None,
+            state: State::Disconnected,
+            reconnects: 0,
+        }
+    }
+
+    pub fn with_handshake<F, Fut>(mut self, handshake: F) -> ReconnectingStream
+    where
+        F: Fn(Stream) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = io::Result<Stream>> + Send + 'static,
+    {
+        self.handshake = Some(Arc::new(move |stream| Box::pin(handshake(stream))));
+        self
+    }
+
+    pub fn named_socket_addr(&self) -> &NamedSocketAddr {
+        &self.named_socket_addr
+    }
+
+    pub fn get_ref(&self) -> Option<&Stream> {
+        match &self.state {
+            State::Connected(stream) => Some(stream),
+            _ => None,
+        }
+    }
+
+    fn connect(&self) -> ConnectFuture {
+        let named_socket_addr = self.named_socket_addr.clone();
+        let retries = self.retries;
+        let initial_backoff = self.initial_backoff;
+        let handshake = self.handshake.clone();
+
+        Box::pin(async move {
+            let stream = Stream::connect_retry(&named_socket_addr, retries, initial_backoff).await?;
+
+            match handshake {
+                Some(handshake) => handshake(stream).await,
+                None => Ok(stream),
+            }
+        })
+    }
+
+    fn poll_connected(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        loop {
+            match &mut self.state {
+                State::Connected(_) => return Poll::Ready(Ok(())),
+                State::Connecting(connect) => match ready!(connect.as_mut().poll(cx)) {
+                    Ok(stream) => self.state = State::Connected(stream),
+                    Err(error) => {
+                        self.state = State::Disconnected;
+                        return Poll::Ready(Err(error))
+                    }
+                },
+                State::Disconnected => self.state = State::Connecting(self.connect()),
+            }
+        }
+    }
+
+    fn poll_io<T, F>(&mut self, cx: &mut Context<'_>, mut operation: F) -> Poll<io::Result<T>>
+    where
+        F: FnMut(Pin<&mut Stream>, &mut Context<'_>) -> Poll<io::Result<T>>,
+    {
+        loop {
+            ready!(self.poll_connected(cx))?;
+
+            let State::Connected(stream) = &mut self.state else {
+                unreachable!()
+            };
+
+            match ready!(operation(Pin::new(stream), cx)) {
+                Err(error) if is_disconnect(&error) && self.reconnects < self.retries => {
+                    self.reconnects += 1;
+                    self.state = State::Disconnected;
+                }
+                Err(error) => return Poll::Ready(Err(error)),
+                Ok(value) => {
+                    self.reconnects = 0;
+                    return Poll::Ready(Ok(value))
+                }
+            }
+        }
+    }
+}
+
+fn is_disconnect(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::BrokenPipe
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::NotConnected
+    )
+}
+
+impl Debug for ReconnectingStream {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReconnectingStream")
+            .field("named_socket_addr", &self.named_socket_addr)
+            .field("stream", &self.get_ref())
+            .finish_non_exhaustive()
+    }
+}
+
+// NOTE: Reaching the end of the stream is reported as usual, only errors cause a reconnect.
+impl AsyncRead for ReconnectingStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::into_inner(self).poll_io(cx, |stream, cx| stream.poll_read(cx, buf))
+    }
+}
+
+impl AsyncWrite for ReconnectingStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::into_inner(self).poll_io(cx, |stream, cx| stream.poll_write(cx, buf))
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::into_inner(self).poll_io(cx, |stream, cx| stream.poll_write_vectored(cx, bufs))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match &mut Pin::into_inner(self).state {
+            State::Connected(stream) => Pin::new(stream).poll_flush(cx),
+            _ => Poll::Ready(Ok(())),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match &mut Pin::into_inner(self).state {
+            State::Connected(stream) => Pin::new(stream).poll_shutdown(cx),
+            _ => Poll::Ready(Ok(())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+    use crate::{test_util, Listener};
+
+    #[tokio::test]
+    async fn reconnects_after_reset() {
+        let (listener, local_addr) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+
+        let mut reconnecting_stream = ReconnectingStream::new(local_addr.to_named_socket_addr().unwrap(), 3, Duration::from_millis(1))
+            .with_handshake(|mut stream| async move {
+                stream.write_all(b"hi").await?;
+                Ok(stream)
+            });
+
+        assert!(reconnecting_stream.get_ref().is_none());
+
+        let server = tokio::spawn(async move {
+            let mut buf = [0; 3];
+
+            let (mut first, _) = listener.accept().await.unwrap();
+            first.read_exact(&mut buf).await.unwrap();
+            assert_eq!(&buf, b"hia");
+
+            // NOTE: Closing without lingering resets the connection.
+            first.with_socket2(|socket| socket.set_linger(Some(Duration::ZERO))).unwrap();
+            drop(first);
+
+            let (mut second, _) = listener.accept().await.unwrap();
+            second.read_exact(&mut buf[..2]).await.unwrap();
+            assert_eq!(&buf[..2], b"hi");
+            second.write_all(b"x").await.unwrap();
+        });
+
+        reconnecting_stream.write_all(b"a").await.unwrap();
+        assert!(reconnecting_stream.get_ref().is_some());
+
+        let mut buf = [0; 1];
+
+        reconnecting_stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"x");
+
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn gives_up_connecting() {
+        let mut reconnecting_stream = ReconnectingStream::new(test_util::unused_tcp_addr().await, 1, Duration::from_millis(1));
+
+        assert_eq!(reconnecting_stream.write_all(b"a").await.unwrap_err().kind(), io::ErrorKind::ConnectionRefused);
+        assert!(reconnecting_stream.get_ref().is_none());
+    }
+}
@@ -0,0 +1,145 @@
+/*
+ * Copyright (c) 2023, networkException <git@nwex.de>
+ *
+ * SPDX-License-Identifier: BSD-2-Clause OR MIT
+ */
+
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+// NOTE: While enabled, reads failing with io::ErrorKind::ConnectionReset end the stream like
+//       a clean close by the peer. This simplifies request loops, but a reset can't be told
+//       apart from a clean close anymore, so a peer that crashed or aborted in the middle of a
+//       message looks like one that finished sending. Writes still report resets.
+#[derive(Debug)]
+pub struct ResetAsEof<S> {
+    inner: S,
+    reset_as_eof: bool,
+}
+
+impl<S> ResetAsEof<S> {
+    pub fn new(inner: S) -> ResetAsEof<S> {
+        ResetAsEof {
+            inner,
+            reset_as_eof: true,
+        }
+    }
+
+    pub fn set_reset_as_eof(&mut self, reset_as_eof: bool) {
+        self.reset_as_eof = reset_as_eof;
+    }
+
+    pub fn reset_as_eof(&self) -> bool {
+        self.reset_as_eof
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for ResetAsEof<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = Pin::into_inner(self);
+
+        match Pin::new(&mut this.inner).poll_read(cx, buf) {
+            // NOTE: Leaving the buffer unfilled is how a read reports the end of the stream.
+            Poll::Ready(Err(error)) if this.reset_as_eof && error.kind() == io::ErrorKind::ConnectionReset => Poll::Ready(Ok(())),
+            poll => poll,
+        }
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for ResetAsEof<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut Pin::into_inner(self).inner).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut Pin::into_inner(self).inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut Pin::into_inner(self).inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut Pin::into_inner(self).inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    use std::time::Duration;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn reads_resets_as_eof() {
+        for reset_as_eof in [true, false] {
+            let (client, server) = test_util::tcp_pair().await;
+            let mut client = ResetAsEof::new(client);
+
+            client.set_reset_as_eof(reset_as_eof);
+            assert_eq!(client.reset_as_eof(), reset_as_eof);
+
+            // NOTE: Closing without lingering resets the connection.
+            server.with_socket2(|socket| socket.set_linger(Some(Duration::ZERO))).unwrap();
+            drop(server);
+
+            let result = client.read(&mut [0; 1]).await;
+
+            match reset_as_eof {
+                true => assert_eq!(result.unwrap(), 0),
+                false => assert_eq!(result.unwrap_err().kind(), io::ErrorKind::ConnectionReset),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn passes_data_through() {
+        let (a, b) = crate::MemoryStream::pair(16);
+        let mut a = ResetAsEof::new(a);
+        let mut b = ResetAsEof::new(b);
+
+        assert!(a.reset_as_eof());
+
+        a.write_all(b"ping").await.unwrap();
+        a.shutdown().await.unwrap();
+
+        let mut buf = Vec::new();
+        b.read_to_end(&mut buf).await.unwrap();
+
+        assert_eq!(buf, b"ping");
+    }
+}
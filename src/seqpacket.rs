@@ -0,0 +1,223 @@
+/*
+ * Copyright (c) 2023, networkException <git@nwex.de>
+ *
+ * SPDX-License-Identifier: BSD-2-Clause OR MIT
+ */
+
+use std::io;
+use std::mem::MaybeUninit;
+use std::net::Shutdown;
+use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, RawFd};
+
+use socket2::{Domain, SockAddr, Socket, Type};
+use tokio::io::unix::AsyncFd;
+use tokio::io::Interest;
+
+use crate::{fd, NamedSocketAddr, SocketAddr, UnixSocketAddr};
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const SEND_FLAGS: libc::c_int = libc::MSG_NOSIGNAL;
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+const SEND_FLAGS: libc::c_int = 0;
+
+// NOTE: Unlike Stream, every send is delivered as one message and every recv returns at
+//       most one message. Parts of a message that don't fit into the buffer passed to
+//       recv are discarded.
+#[derive(Debug)]
+pub struct SeqPacket {
+    inner: AsyncFd<Socket>,
+}
+
+#[derive(Debug)]
+pub struct SeqPacketListener {
+    inner: AsyncFd<Socket>,
+}
+
+impl SeqPacket {
+    pub async fn connect(named_socket_addr: &NamedSocketAddr) -> io::Result<SeqPacket> {
+        let sock_addr = unix_sock_addr(named_socket_addr)?;
+        let socket = Socket::new(Domain::UNIX, seqpacket_type(), None)?;
+
+        socket.set_nonblocking(true)?;
+
+        let inner = AsyncFd::new(socket)?;
+
+        fd::connect(&inner, &sock_addr).await?;
+
+        Ok(SeqPacket { inner })
+    }
+
+    fn from_socket(socket: Socket) -> io::Result<SeqPacket> {
+        socket.set_nonblocking(true)?;
+
+        Ok(SeqPacket { inner: AsyncFd::new(socket)? })
+    }
+
+    pub async fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        self.inner
+            .async_io(Interest::WRITABLE, |socket| socket.send_with_flags(buf, SEND_FLAGS))
+            .await
+    }
+
+    pub async fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        // SAFETY: recv only ever writes initialized bytes into the buffer.
+        let buf = unsafe { &mut *(buf as *mut [u8] as *mut [MaybeUninit<u8>]) };
+
+        self.inner
+            .async_io(Interest::READABLE, |socket| socket.recv(buf))
+            .await
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.get_ref().local_addr().map(|sock_addr| SocketAddr::Unix(unix_socket_addr(&sock_addr)))
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.get_ref().peer_addr().map(|sock_addr| SocketAddr::Unix(unix_socket_addr(&sock_addr)))
+    }
+
+    pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        self.inner.get_ref().shutdown(how)
+    }
+}
+
+impl SeqPacketListener {
+    pub fn bind(named_socket_addr: &NamedSocketAddr) -> io::Result<SeqPacketListener> {
+        let sock_addr = unix_sock_addr(named_socket_addr)?;
+        let socket = Socket::new(Domain::UNIX, seqpacket_type(), None)?;
+
+        socket.bind(&sock_addr)?;
+        socket.listen(1024)?;
+        socket.set_nonblocking(true)?;
+
+        Ok(SeqPacketListener { inner: AsyncFd::new(socket)? })
+    }
+
+    pub async fn accept(&self) -> io::Result<(SeqPacket, SocketAddr)> {
+        let (socket, sock_addr) = self.inner
+            .async_io(Interest::READABLE, |socket| socket.accept())
+            .await?;
+
+        Ok((SeqPacket::from_socket(socket)?, SocketAddr::Unix(unix_socket_addr(&sock_addr))))
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.get_ref().local_addr().map(|sock_addr| SocketAddr::Unix(unix_socket_addr(&sock_addr)))
+    }
+}
+
+impl AsRawFd for SeqPacket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+impl AsFd for SeqPacket {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.inner.get_ref().as_fd()
+    }
+}
+
+impl AsRawFd for SeqPacketListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+impl AsFd for SeqPacketListener {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.inner.get_ref().as_fd()
+    }
+}
+
+// NOTE: socket2 only exposes Type::SEQPACKET with its "all" feature.
+fn seqpacket_type() -> Type {
+    Type::from(libc::SOCK_SEQPACKET)
+}
+
+fn unix_sock_addr(named_socket_addr: &NamedSocketAddr) -> io::Result<SockAddr> {
+    match named_socket_addr {
+        NamedSocketAddr::Unix(path) => SockAddr::unix(path),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Cannot use a seqpacket socket with a non unix address.",
+        )),
+    }
+}
+
+fn unix_socket_addr(sock_addr: &SockAddr) -> UnixSocketAddr {
+    if let Some(path) = sock_addr.as_pathname() {
+        return UnixSocketAddr::Pathname(path.to_path_buf())
+    }
+
+    if let Some(name) = sock_addr.as_abstract_namespace() {
+        return UnixSocketAddr::Abstract(name.to_vec())
+    }
+
+    UnixSocketAddr::Unnamed
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::test_util;
+
+    #[tokio::test]
+    async fn keeps_message_boundaries() {
+        let path = test_util::temp_path("seqpacket.sock");
+        let named_socket_addr = NamedSocketAddr::unix(&path);
+        let listener = SeqPacketListener::bind(&named_socket_addr).unwrap();
+
+        assert_eq!(listener.local_addr().unwrap(), SocketAddr::unix(&path));
+
+        let (client, accepted) = tokio::join!(SeqPacket::connect(&named_socket_addr), listener.accept());
+        let (client, (server, _)) = (client.unwrap(), accepted.unwrap());
+
+        assert_eq!(client.peer_addr().unwrap(), SocketAddr::unix(&path));
+        assert_eq!(server.local_addr().unwrap(), SocketAddr::unix(&path));
+
+        assert_eq!(client.send(b"hello").await.unwrap(), 5);
+        assert_eq!(client.send(b"world").await.unwrap(), 5);
+
+        // NOTE: The rest of a message that doesn't fit is discarded.
+        let mut buf = [0; 16];
+
+        assert_eq!(server.recv(&mut buf[..3]).await.unwrap(), 3);
+        assert_eq!(&buf[..3], b"hel");
+        assert_eq!(server.recv(&mut buf).await.unwrap(), 5);
+        assert_eq!(&buf[..5], b"world");
+
+        client.shutdown(Shutdown::Write).unwrap();
+        assert_eq!(server.recv(&mut buf).await.unwrap(), 0);
+
+        let error = SeqPacket::connect(&NamedSocketAddr::from((test_util::LOCALHOST, 80))).await.unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(SeqPacketListener::bind(&named_socket_addr).unwrap_err().kind(), io::ErrorKind::AddrInUse);
+    }
+
+    // NOTE: Connecting to a listener with a full backlog fails with EAGAIN instead of
+    //       waiting, SeqPacket::connect has to retry until there is room again.
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn connect_waits_for_backlog() {
+        let path = test_util::temp_path("backlog.sock");
+        let named_socket_addr = NamedSocketAddr::unix(&path);
+        let socket = Socket::new(Domain::UNIX, seqpacket_type(), None).unwrap();
+
+        socket.bind(&SockAddr::unix(&path).unwrap()).unwrap();
+        socket.listen(0).unwrap();
+
+        let _pending = SeqPacket::connect(&named_socket_addr).await.unwrap();
+        let connect = tokio::spawn(async move { SeqPacket::connect(&named_socket_addr).await });
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!connect.is_finished());
+
+        socket.accept().unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), connect).await.unwrap().unwrap().unwrap();
+    }
+}
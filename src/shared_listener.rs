@@ -0,0 +1,80 @@
+/*
+ * Copyright (c) 2023, networkException <git@nwex.de>
+ *
+ * SPDX-License-Identifier: BSD-2-Clause OR MIT
+ */
+
+use std::io;
+use std::sync::Arc;
+
+use crate::{Listener, SocketAddr, Stream};
+
+// NOTE: Clones accept from the same underlying socket, concurrent accepts are
+//       distributed between them by the kernel.
+#[derive(Debug, Clone)]
+pub struct SharedListener {
+    inner: Arc<Listener>,
+}
+
+impl SharedListener {
+    pub fn new(listener: Listener) -> SharedListener {
+        SharedListener {
+            inner: Arc::new(listener),
+        }
+    }
+
+    pub fn get_ref(&self) -> &Listener {
+        &self.inner
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+
+    pub async fn accept(&self) -> io::Result<(Stream, SocketAddr)> {
+        self.inner.accept().await
+    }
+}
+
+impl From<Listener> for SharedListener {
+    fn from(listener: Listener) -> SharedListener {
+        SharedListener::new(listener)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    #[tokio::test]
+    async fn clones_accept_concurrently() {
+        let (listener, local_addr) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+        let shared_listener = SharedListener::from(listener);
+
+        assert_eq!(shared_listener.local_addr().unwrap(), local_addr);
+
+        let accepts = (0..2)
+            .map(|_| {
+                let shared_listener = shared_listener.clone();
+
+                tokio::spawn(async move { shared_listener.accept().await.unwrap().1 })
+            })
+            .collect::<Vec<_>>();
+
+        let named_socket_addr = local_addr.to_named_socket_addr().unwrap();
+        let clients = [Stream::connect(&named_socket_addr).await.unwrap(), Stream::connect(&named_socket_addr).await.unwrap()];
+
+        let mut peer_addrs = Vec::new();
+
+        for accept in accepts {
+            peer_addrs.push(accept.await.unwrap());
+        }
+
+        let mut local_addrs = clients.iter().map(|client| client.local_addr().unwrap()).collect::<Vec<_>>();
+
+        peer_addrs.sort();
+        local_addrs.sort();
+        assert_eq!(peer_addrs, local_addrs);
+    }
+}
@@ -5,85 +5,418 @@
  */
 
 use std::io;
+use std::error;
 use std::fmt::{self, Debug, Display, Formatter};
-use std::net::{self, AddrParseError};
+use std::net::{self, AddrParseError, IpAddr, Ipv4Addr, Ipv6Addr};
 use std::str::FromStr;
-// NOTE: PathBuf is used in the signature of functions that also need to
-//       be available on non unix systems (at least for a noop).
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "net")]
+use tokio::net::lookup_host;
+#[cfg(all(feature = "net", unix))]
+use {std::{fs, os::unix::fs::FileTypeExt}, tokio::net::UnixStream};
+
+use crate::Transport;
 
 #[cfg(unix)]
-use tokio::net::unix;
+use std::{ffi::{CStr, CString, OsString}, fmt::Write, mem, os::unix::{ffi::{OsStrExt, OsStringExt}, io::RawFd}};
+
+#[cfg(all(feature = "net", target_os = "android"))]
+use std::os::android::net::SocketAddrExt;
+#[cfg(all(feature = "net", target_os = "linux"))]
+use std::os::linux::net::SocketAddrExt;
+
+#[cfg(all(feature = "net", unix))]
+use {std::os::unix::net::SocketAddr as StdUnixSocketAddr, tokio::net::unix};
 
 #[cfg(feature = "serde")]
-use serde::{Serialize, Deserialize, Deserializer, de::Error};
+use serde::{Serialize, Serializer, Deserialize, Deserializer, de::Error};
 
-// NOTE: This enum is used in the signature of functions that also need to
-//       be available on non unix systems (at least for a noop).
+const UNIX_SCHEME: &str = "unix:";
+const TCP_SCHEME: &str = "tcp://";
+// NOTE: Accepted when parsing for compatibility with the scheme used by older versions.
+const LEGACY_TCP_SCHEME: &str = "tcp:";
+const ABSTRACT_PREFIX: &str = "@";
+// NOTE: sun_path is 108 bytes on Linux, the leading null byte of abstract names takes one.
+const MAX_ABSTRACT_NAME_LEN: usize = 107;
+const UNNAMED: &str = "(unnamed)";
+#[cfg(target_os = "linux")]
+const INHERITED_FD_PREFIXES: [&str; 2] = ["/proc/self/fd/", "/dev/fd/"];
+#[cfg(windows)]
+const PIPE_PREFIX: &str = r"\\.\pipe\";
+#[cfg(all(feature = "vsock", target_os = "linux"))]
+const VSOCK_SCHEME: &str = "vsock:";
+
+// NOTE: The unix variants of the address types are also available on non unix
+//       systems so matching on them doesn't require cfg attributes. Binding or
+//       connecting to them fails with io::ErrorKind::Unsupported there.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum UnixSocketAddr {
-    #[cfg(unix)]
-    AbstractOrUnnamed,
-    #[cfg(unix)]
+    Abstract(Vec<u8>),
+    Unnamed,
     Pathname(PathBuf),
 }
 
-#[cfg(unix)]
 impl UnixSocketAddr {
     pub fn is_pathname(input: &str) -> bool {
         input.starts_with('/') || input.starts_with('.')
     }
+
+    pub fn is_unnamed(&self) -> bool {
+        matches!(self, UnixSocketAddr::Unnamed)
+    }
+
+    pub fn as_pathname(&self) -> Option<&Path> {
+        match self {
+            UnixSocketAddr::Pathname(path) => Some(path),
+            _ => None,
+        }
+    }
+
+    // NOTE: The name excludes the leading null byte. Abstract sockets only exist on Linux.
+    pub fn as_abstract_name(&self) -> Option<&[u8]> {
+        match self {
+            UnixSocketAddr::Abstract(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    #[cfg(all(feature = "net", unix))]
+    pub fn to_tokio(&self) -> io::Result<unix::SocketAddr> {
+        match self {
+            UnixSocketAddr::Pathname(path) => StdUnixSocketAddr::from_pathname(path).map(unix::SocketAddr::from),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            UnixSocketAddr::Abstract(name) => StdUnixSocketAddr::from_abstract_name(name).map(unix::SocketAddr::from),
+            #[cfg(not(any(target_os = "linux", target_os = "android")))]
+            UnixSocketAddr::Abstract(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot create an abstract unix socket address on this system.",
+            )),
+            UnixSocketAddr::Unnamed => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Cannot create an address for an unnamed unix socket.",
+            )),
+        }
+    }
+
+    // Fills a sockaddr_un for passing to C code, returning it with the length to pass along.
+    // Pathnames are null terminated and the length only covers the used part of sun_path,
+    // abstract names start with a null byte and aren't terminated. For unnamed sockets
+    // only the address family is filled in.
+    #[cfg(unix)]
+    pub fn to_sockaddr_bytes(&self) -> io::Result<(libc::sockaddr_un, libc::socklen_t)> {
+        let mut sockaddr: libc::sockaddr_un = unsafe { mem::zeroed() };
+        sockaddr.sun_family = libc::AF_UNIX as libc::sa_family_t;
+
+        let (offset, name, terminator): (usize, &[u8], usize) = match self {
+            UnixSocketAddr::Pathname(path) => {
+                let bytes = path.as_os_str().as_bytes();
+
+                if bytes.contains(&0) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "Cannot create an address for a path containing a null byte.",
+                    ))
+                }
+
+                // NOTE: Leaves room for the terminating null byte.
+                if bytes.len() >= sockaddr.sun_path.len() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "Cannot create an address for a path longer than sun_path.",
+                    ))
+                }
+
+                (0, bytes, 1)
+            }
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            UnixSocketAddr::Abstract(name) => {
+                if name.len() >= sockaddr.sun_path.len() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "Cannot create an address for an abstract name longer than sun_path.",
+                    ))
+                }
+
+                (1, name, 0)
+            }
+            #[cfg(not(any(target_os = "linux", target_os = "android")))]
+            UnixSocketAddr::Abstract(_) => return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot create an abstract unix socket address on this system.",
+            )),
+            UnixSocketAddr::Unnamed => (0, &[], 0),
+        };
+
+        for (target, byte) in sockaddr.sun_path[offset..].iter_mut().zip(name) {
+            *target = *byte as libc::c_char;
+        }
+
+        let length = mem::offset_of!(libc::sockaddr_un, sun_path) + offset + name.len() + terminator;
+
+        // NOTE: BSD derived systems store the length of the address in the address itself.
+        #[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd", target_os = "dragonfly"))]
+        {
+            sockaddr.sun_len = length as u8;
+        }
+
+        Ok((sockaddr, length as libc::socklen_t))
+    }
+
+    // Paths that would not be recognized by the FromStr heuristic are prefixed
+    // with the unix scheme so they still round trip.
+    fn fmt_pathname(path: &Path, formatter: &mut Formatter) -> fmt::Result {
+        let escaped = escape_path(path);
+
+        if UnixSocketAddr::is_pathname(&escaped) && !formatter.alternate() {
+            write!(formatter, "{}", escaped)
+        } else {
+            write!(formatter, "{}{}", UNIX_SCHEME, escaped)
+        }
+    }
 }
 
+// NOTE: Non exhaustive as the variants depend on the platform and enabled features.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
 pub enum SocketAddr {
     Inet(net::SocketAddr),
-    #[cfg(unix)]
     Unix(UnixSocketAddr),
+    #[cfg(windows)]
+    Pipe(PathBuf),
+    #[cfg(all(feature = "vsock", target_os = "linux"))]
+    Vsock { cid: u32, port: u32 },
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
 pub enum NamedSocketAddr {
     Inet(net::SocketAddr),
-    #[cfg(unix)]
     Unix(PathBuf),
+    #[cfg(windows)]
+    Pipe(PathBuf),
+    #[cfg(all(feature = "vsock", target_os = "linux"))]
+    Vsock { cid: u32, port: u32 },
+}
+
+// NOTE: Like NamedSocketAddr, but can also represent abstract unix socket names, which
+//       can be bound to but not be connected to using a NamedSocketAddr.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum BindableAddr {
+    Named(NamedSocketAddr),
+    Abstract(Vec<u8>),
 }
 
 impl SocketAddr {
+    // See NamedSocketAddr::unix.
+    pub fn unix<P: AsRef<Path>>(path: P) -> SocketAddr {
+        SocketAddr::Unix(UnixSocketAddr::Pathname(path.as_ref().to_path_buf()))
+    }
+
+    pub fn is_inet(&self) -> bool {
+        match self {
+            SocketAddr::Inet(_) => true,
+            SocketAddr::Unix(_) => false,
+            #[cfg(windows)]
+            SocketAddr::Pipe(_) => false,
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            SocketAddr::Vsock { .. } => false,
+        }
+    }
+
+    pub fn is_unix(&self) -> bool {
+        match self {
+            SocketAddr::Inet(_) => false,
+            SocketAddr::Unix(_) => true,
+            #[cfg(windows)]
+            SocketAddr::Pipe(_) => false,
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            SocketAddr::Vsock { .. } => false,
+        }
+    }
+
+    pub fn transport(&self) -> Transport {
+        match self {
+            SocketAddr::Inet(_) => Transport::Tcp,
+            SocketAddr::Unix(_) => Transport::Unix,
+            #[cfg(windows)]
+            SocketAddr::Pipe(_) => Transport::Pipe,
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            SocketAddr::Vsock { .. } => Transport::Vsock,
+        }
+    }
+
+    pub fn as_inet(&self) -> Option<net::SocketAddr> {
+        match self {
+            SocketAddr::Inet(inet_socket_addr) => Some(*inet_socket_addr),
+            SocketAddr::Unix(_) => None,
+            #[cfg(windows)]
+            SocketAddr::Pipe(_) => None,
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            SocketAddr::Vsock { .. } => None,
+        }
+    }
+
+    pub fn as_unix(&self) -> Option<&UnixSocketAddr> {
+        match self {
+            SocketAddr::Inet(_) => None,
+            SocketAddr::Unix(unix_socket_addr) => Some(unix_socket_addr),
+            #[cfg(windows)]
+            SocketAddr::Pipe(_) => None,
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            SocketAddr::Vsock { .. } => None,
+        }
+    }
+
+    pub fn port(&self) -> Option<u16> {
+        self.as_inet().map(|inet_socket_addr| inet_socket_addr.port())
+    }
+
+    pub fn ip(&self) -> Option<IpAddr> {
+        self.as_inet().map(|inet_socket_addr| inet_socket_addr.ip())
+    }
+
     pub fn map_inet<F: FnOnce(net::SocketAddr) -> net::SocketAddr>(self, mapper: F) -> SocketAddr {
         match self {
             SocketAddr::Inet(inet_socket_addr) => SocketAddr::Inet(mapper(inet_socket_addr)),
-            #[cfg(unix)]
             SocketAddr::Unix(unix_socket_addr) => SocketAddr::Unix(unix_socket_addr),
+            #[cfg(windows)]
+            SocketAddr::Pipe(pipe_name) => SocketAddr::Pipe(pipe_name),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            SocketAddr::Vsock { cid, port } => SocketAddr::Vsock { cid, port },
         }
     }
 
-    // On non unix systems, op is not used.
-    #[cfg_attr(not(unix), allow(unused_variables))]
     pub fn map_unix<F: FnOnce(UnixSocketAddr) -> UnixSocketAddr>(self, mapper: F) -> SocketAddr {
         match self {
             SocketAddr::Inet(inet_socket_addr) => SocketAddr::Inet(inet_socket_addr),
-            #[cfg(unix)]
             SocketAddr::Unix(unix_socket_addr) => SocketAddr::Unix(mapper(unix_socket_addr)),
+            #[cfg(windows)]
+            SocketAddr::Pipe(pipe_name) => SocketAddr::Pipe(pipe_name),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            SocketAddr::Vsock { cid, port } => SocketAddr::Vsock { cid, port },
+        }
+    }
+
+    #[cfg(feature = "net")]
+    pub async fn resolve(input: &str) -> io::Result<Vec<SocketAddr>> {
+        if input.starts_with(UNIX_SCHEME) || UnixSocketAddr::is_pathname(input) {
+            return SocketAddr::from_str(input)
+                .map(|socket_addr| vec![socket_addr])
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))
+        }
+
+        #[cfg(windows)]
+        if input.starts_with(PIPE_PREFIX) {
+            return Ok(vec![SocketAddr::Pipe(PathBuf::from(input))])
+        }
+
+        #[cfg(all(feature = "vsock", target_os = "linux"))]
+        if input.starts_with(VSOCK_SCHEME) {
+            return SocketAddr::from_str(input)
+                .map(|socket_addr| vec![socket_addr])
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))
+        }
+
+        let host = strip_tcp_scheme(input).unwrap_or(input);
+
+        Ok(lookup_host(host).await?.map(SocketAddr::Inet).collect())
+    }
+
+    pub fn to_bindable(self) -> io::Result<BindableAddr> {
+        match self {
+            SocketAddr::Unix(UnixSocketAddr::Abstract(name)) => Ok(BindableAddr::Abstract(name)),
+            SocketAddr::Unix(UnixSocketAddr::Unnamed) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot bind to an unnamed unix socket.",
+            )),
+            socket_addr => socket_addr.to_named_socket_addr().map(BindableAddr::Named),
         }
     }
 
     pub fn to_named_socket_addr(self) -> io::Result<NamedSocketAddr> {
         match self {
             SocketAddr::Inet(inet_socket_addr) => Ok(NamedSocketAddr::Inet(inet_socket_addr)),
-            #[cfg(unix)]
             SocketAddr::Unix(UnixSocketAddr::Pathname(pathname)) => Ok(NamedSocketAddr::Unix(pathname)),
-            #[cfg(unix)]
-            SocketAddr::Unix(UnixSocketAddr::AbstractOrUnnamed) => Err(io::Error::new(
+            SocketAddr::Unix(UnixSocketAddr::Abstract(_)) => Err(io::Error::new(
                 io::ErrorKind::Unsupported,
-                "Cannot connect to a abstract or unnamed unix socket.",
-            ))
+                "Cannot connect to an abstract unix socket.",
+            )),
+            SocketAddr::Unix(UnixSocketAddr::Unnamed) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot connect to an unnamed unix socket.",
+            )),
+            #[cfg(windows)]
+            SocketAddr::Pipe(pipe_name) => Ok(NamedSocketAddr::Pipe(pipe_name)),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            SocketAddr::Vsock { cid, port } => Ok(NamedSocketAddr::Vsock { cid, port }),
+        }
+    }
+
+    // A label for grouping connections by client in logs and metrics, which leaves out the
+    // ephemeral port of TCP peers. IPv4-mapped IPv6 addresses are grouped with their IPv4
+    // address. Unix peers are grouped by their path, abstract and unnamed ones together
+    // as (unix-local) since their names usually differ per connection.
+    pub fn logging_key(&self) -> String {
+        self.to_logging_key(false)
+    }
+
+    // Like logging_key but keeps the port, for labelling single connections.
+    pub fn logging_key_with_port(&self) -> String {
+        self.to_logging_key(true)
+    }
+
+    fn to_logging_key(&self, include_port: bool) -> String {
+        match self {
+            SocketAddr::Inet(inet_socket_addr) => {
+                let ip = inet_socket_addr.ip().to_canonical();
+
+                if include_port {
+                    net::SocketAddr::new(ip, inet_socket_addr.port()).to_string()
+                } else {
+                    ip.to_string()
+                }
+            }
+            SocketAddr::Unix(UnixSocketAddr::Pathname(path)) => path.display().to_string(),
+            SocketAddr::Unix(UnixSocketAddr::Abstract(_) | UnixSocketAddr::Unnamed) => "(unix-local)".to_string(),
+            #[cfg(windows)]
+            SocketAddr::Pipe(pipe_name) => pipe_name.display().to_string(),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            SocketAddr::Vsock { cid, port } => {
+                if include_port {
+                    format!("{}{}:{}", VSOCK_SCHEME, cid, port)
+                } else {
+                    format!("{}{}", VSOCK_SCHEME, cid)
+                }
+            }
         }
     }
 
+    #[cfg_attr(feature = "serde", allow(unused))]
+    #[cfg(feature = "serde")]
+    pub fn serialize_as_str<S>(socket_addr: &SocketAddr, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(socket_addr)
+    }
+
+    #[cfg_attr(feature = "serde", allow(unused))]
+    #[cfg(feature = "serde")]
+    pub fn serialize_as_option_str<S>(option: &Option<SocketAddr>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[derive(Serialize)]
+        struct Helper<'a>(#[serde(serialize_with = "SocketAddr::serialize_as_str")] &'a SocketAddr);
+
+        option.as_ref().map(Helper).serialize(serializer)
+    }
+
     #[cfg_attr(feature = "serde", allow(unused))]
     #[cfg(feature = "serde")]
     pub fn deserialize_from_str<'de, D>(deserializer: D) -> Result<SocketAddr, D::Error>
@@ -109,32 +442,140 @@ impl SocketAddr {
 }
 
 impl NamedSocketAddr {
+    // Builds a Unix socket address from any path, without the heuristic used when parsing
+    // from a string. Relative paths like app.sock are therefore never treated as TCP.
+    pub fn unix<P: AsRef<Path>>(path: P) -> NamedSocketAddr {
+        NamedSocketAddr::Unix(path.as_ref().to_path_buf())
+    }
+
+    // Refers to a socket inherited by the process (e.g. from a service manager), binding or
+    // connecting to it adopts a duplicate of fd instead of using the filesystem.
+    #[cfg(target_os = "linux")]
+    pub fn from_fd(fd: RawFd) -> NamedSocketAddr {
+        NamedSocketAddr::Unix(PathBuf::from(format!("{}{}", INHERITED_FD_PREFIXES[0], fd)))
+    }
+
+    // NOTE: Paths like /proc/self/fd/3 or /dev/fd/3 refer to a socket inherited by the
+    //       process, which can't be bound to or connected to like a regular path. Only
+    //       Linux exposes sockets this way, elsewhere this always returns None.
+    #[cfg(unix)]
+    pub fn inherited_fd(&self) -> Option<RawFd> {
+        match self {
+            #[cfg(target_os = "linux")]
+            NamedSocketAddr::Unix(path) => INHERITED_FD_PREFIXES
+                .iter()
+                .find_map(|prefix| path.to_str()?.strip_prefix(prefix))
+                .and_then(|fd| fd.parse().ok()),
+            _ => None,
+        }
+    }
+
     pub fn map_inet<F: FnOnce(net::SocketAddr) -> net::SocketAddr>(self, mapper: F) -> NamedSocketAddr {
         match self {
             NamedSocketAddr::Inet(inet_socket_addr) => NamedSocketAddr::Inet(mapper(inet_socket_addr)),
-            #[cfg(unix)]
             NamedSocketAddr::Unix(path) => NamedSocketAddr::Unix(path),
+            #[cfg(windows)]
+            NamedSocketAddr::Pipe(pipe_name) => NamedSocketAddr::Pipe(pipe_name),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            NamedSocketAddr::Vsock { cid, port } => NamedSocketAddr::Vsock { cid, port },
         }
     }
 
-    // On non unix systems, op is not used.
-    #[cfg_attr(not(unix), allow(unused_variables))]
     pub fn map_unix<F: FnOnce(PathBuf) -> PathBuf>(self, mapper: F) -> NamedSocketAddr {
         match self {
             NamedSocketAddr::Inet(inet_socket_addr) => NamedSocketAddr::Inet(inet_socket_addr),
-            #[cfg(unix)]
             NamedSocketAddr::Unix(path) => NamedSocketAddr::Unix(mapper(path)),
+            #[cfg(windows)]
+            NamedSocketAddr::Pipe(pipe_name) => NamedSocketAddr::Pipe(pipe_name),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            NamedSocketAddr::Vsock { cid, port } => NamedSocketAddr::Vsock { cid, port },
+        }
+    }
+
+    // A Unix socket is considered stale if its file exists but connecting to it is
+    // refused, meaning no process is listening on it anymore.
+    #[cfg(feature = "net")]
+    pub async fn is_stale_unix_socket(&self) -> io::Result<bool> {
+        match self {
+            #[cfg(unix)]
+            NamedSocketAddr::Unix(path) => {
+                match fs::symlink_metadata(path) {
+                    Ok(metadata) if metadata.file_type().is_socket() => {}
+                    Ok(_) => return Ok(false),
+                    Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(false),
+                    Err(error) => return Err(error),
+                }
+
+                match UnixStream::connect(path).await {
+                    Ok(_) => Ok(false),
+                    Err(error) if error.kind() == io::ErrorKind::ConnectionRefused => Ok(true),
+                    Err(error) => Err(error),
+                }
+            }
+            _ => Ok(false),
+        }
+    }
+
+    // Returns the type of the filesystem a Unix socket path is on (e.g. tmpfs), so services
+    // can warn about sockets in places that are cleaned up while they run. Before binding the
+    // parent directory is inspected instead. Other addresses return None.
+    #[cfg(target_os = "linux")]
+    pub fn socket_path_filesystem(&self) -> io::Result<Option<String>> {
+        match self {
+            NamedSocketAddr::Unix(path) => {
+                let path = match path.parent() {
+                    _ if path.exists() => path.as_path(),
+                    Some(parent) if !parent.as_os_str().is_empty() => parent,
+                    _ => Path::new("."),
+                };
+
+                filesystem_type(path).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    // NOTE: This resolves Unix pathnames through the filesystem, which requires the
+    //       socket file to exist. Other addresses are returned unchanged.
+    pub fn canonicalize(&self) -> io::Result<NamedSocketAddr> {
+        match self {
+            NamedSocketAddr::Unix(path) => path.canonicalize().map(NamedSocketAddr::Unix),
+            _ => Ok(self.clone()),
         }
     }
 
     pub fn to_socket_addr(self) -> SocketAddr {
         match self {
             NamedSocketAddr::Inet(inet_socket_addr) => SocketAddr::Inet(inet_socket_addr),
-            #[cfg(unix)]
             NamedSocketAddr::Unix(path) => SocketAddr::Unix(UnixSocketAddr::Pathname(path)),
+            #[cfg(windows)]
+            NamedSocketAddr::Pipe(pipe_name) => SocketAddr::Pipe(pipe_name),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            NamedSocketAddr::Vsock { cid, port } => SocketAddr::Vsock { cid, port },
         }
     }
 
+    #[cfg_attr(feature = "serde", allow(unused))]
+    #[cfg(feature = "serde")]
+    pub fn serialize_as_str<S>(named_socket_addr: &NamedSocketAddr, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(named_socket_addr)
+    }
+
+    #[cfg_attr(feature = "serde", allow(unused))]
+    #[cfg(feature = "serde")]
+    pub fn serialize_as_option_str<S>(option: &Option<NamedSocketAddr>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[derive(Serialize)]
+        struct Helper<'a>(#[serde(serialize_with = "NamedSocketAddr::serialize_as_str")] &'a NamedSocketAddr);
+
+        option.as_ref().map(Helper).serialize(serializer)
+    }
+
     #[cfg_attr(feature = "serde", allow(unused))]
     #[cfg(feature = "serde")]
     pub fn deserialize_from_str<'de, D>(deserializer: D) -> Result<NamedSocketAddr, D::Error>
@@ -159,117 +600,1153 @@ impl NamedSocketAddr {
     }
 }
 
-impl FromStr for SocketAddr {
-    type Err = AddrParseError;
+// NOTE: Modules for #[serde(with = "tokio_unix_tcp::socket_addr::as_str")], pairing the
+//       serialize_as_* and deserialize_from_* functions above.
+#[cfg(feature = "serde")]
+pub mod as_str {
+    use serde::{Deserializer, Serializer};
 
-    fn from_str(string: &str) -> Result<Self, Self::Err> {
-        NamedSocketAddr::from_str(string).map(NamedSocketAddr::into)
+    use super::SocketAddr;
+
+    pub fn serialize<S: Serializer>(value: &SocketAddr, serializer: S) -> Result<S::Ok, S::Error> {
+        SocketAddr::serialize_as_str(value, serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SocketAddr, D::Error> {
+        SocketAddr::deserialize_from_str(deserializer)
     }
 }
 
-impl FromStr for NamedSocketAddr {
-    type Err = AddrParseError;
+#[cfg(feature = "serde")]
+pub mod as_opt_str {
+    use serde::{Deserializer, Serializer};
 
-    fn from_str(string: &str) -> Result<Self, Self::Err> {
-        #[cfg(unix)]
-        if UnixSocketAddr::is_pathname(string) {
-            return Ok(NamedSocketAddr::Unix(PathBuf::from_str(string).unwrap()))
-        }
+    use super::SocketAddr;
 
-        Ok(NamedSocketAddr::Inet(net::SocketAddr::from_str(string)?))
+    pub fn serialize<S: Serializer>(value: &Option<SocketAddr>, serializer: S) -> Result<S::Ok, S::Error> {
+        SocketAddr::serialize_as_option_str(value, serializer)
     }
-}
 
-impl From<NamedSocketAddr> for SocketAddr {
-    fn from(named_socket_addr: NamedSocketAddr) -> Self {
-        named_socket_addr.to_socket_addr()
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<SocketAddr>, D::Error> {
+        SocketAddr::deserialize_from_option_str(deserializer)
     }
 }
 
-impl TryFrom<SocketAddr> for NamedSocketAddr {
-    type Error = io::Error;
+#[cfg(feature = "serde")]
+pub mod named_as_str {
+    use serde::{Deserializer, Serializer};
 
-    fn try_from(socket_addr: SocketAddr) -> Result<Self, Self::Error> {
-        socket_addr.to_named_socket_addr()
+    use super::NamedSocketAddr;
+
+    pub fn serialize<S: Serializer>(value: &NamedSocketAddr, serializer: S) -> Result<S::Ok, S::Error> {
+        NamedSocketAddr::serialize_as_str(value, serializer)
     }
-}
 
-impl From<net::SocketAddr> for SocketAddr {
-    fn from(inet_socket_addr: net::SocketAddr) -> SocketAddr {
-        SocketAddr::Inet(inet_socket_addr)
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NamedSocketAddr, D::Error> {
+        NamedSocketAddr::deserialize_from_str(deserializer)
     }
 }
 
-impl From<net::SocketAddr> for NamedSocketAddr {
-    fn from(inet_socket_addr: net::SocketAddr) -> NamedSocketAddr {
-        NamedSocketAddr::Inet(inet_socket_addr)
+#[cfg(feature = "serde")]
+pub mod named_as_opt_str {
+    use serde::{Deserializer, Serializer};
+
+    use super::NamedSocketAddr;
+
+    pub fn serialize<S: Serializer>(value: &Option<NamedSocketAddr>, serializer: S) -> Result<S::Ok, S::Error> {
+        NamedSocketAddr::serialize_as_option_str(value, serializer)
     }
-}
 
-#[cfg(unix)]
-impl From<PathBuf> for SocketAddr {
-    fn from(path: PathBuf) -> SocketAddr {
-        SocketAddr::Unix(UnixSocketAddr::Pathname(path))
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<NamedSocketAddr>, D::Error> {
+        NamedSocketAddr::deserialize_from_option_str(deserializer)
     }
 }
 
-#[cfg(unix)]
-impl From<PathBuf> for NamedSocketAddr {
-    fn from(path: PathBuf) -> NamedSocketAddr {
-        NamedSocketAddr::Unix(path)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseSocketAddrError {
+    Inet(AddrParseError),
+    EmptyPath,
+    InvalidPath,
+    InvalidAbstractName,
+    InvalidVsockAddr,
+}
+
+impl Display for ParseSocketAddrError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            ParseSocketAddrError::Inet(addr_parse_error) => write!(formatter, "{}", addr_parse_error),
+            ParseSocketAddrError::EmptyPath => write!(formatter, "unix socket path is empty"),
+            ParseSocketAddrError::InvalidPath => write!(formatter, "invalid escape sequence in unix socket path"),
+            ParseSocketAddrError::InvalidAbstractName => write!(formatter, "invalid abstract unix socket name"),
+            ParseSocketAddrError::InvalidVsockAddr => write!(formatter, "invalid vsock address syntax"),
+        }
     }
 }
 
-#[cfg(unix)]
-impl From<UnixSocketAddr> for SocketAddr {
-    fn from(unix_socket_addr: UnixSocketAddr) -> SocketAddr {
-        SocketAddr::Unix(unix_socket_addr)
+impl error::Error for ParseSocketAddrError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ParseSocketAddrError::Inet(addr_parse_error) => Some(addr_parse_error),
+            _ => None,
+        }
     }
 }
 
-#[cfg(unix)]
-impl From<unix::SocketAddr> for SocketAddr {
-    fn from(unix_socket_addr: unix::SocketAddr) -> Self {
-        SocketAddr::Unix(unix_socket_addr.into())
+impl From<AddrParseError> for ParseSocketAddrError {
+    fn from(addr_parse_error: AddrParseError) -> Self {
+        ParseSocketAddrError::Inet(addr_parse_error)
     }
 }
 
-#[cfg(unix)]
-impl From<unix::SocketAddr> for UnixSocketAddr {
-    fn from(unix_socket_addr: unix::SocketAddr) -> Self {
-        match unix_socket_addr.as_pathname() {
-            Some(path) => UnixSocketAddr::Pathname(path.to_path_buf()),
-            None => UnixSocketAddr::AbstractOrUnnamed,
+// NOTE: Unlike NamedSocketAddr, this also parses the @name form of abstract addresses
+//       and (unnamed), so every address displayed round trips.
+impl FromStr for SocketAddr {
+    type Err = ParseSocketAddrError;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        if let Some(name) = string.strip_prefix(ABSTRACT_PREFIX) {
+            return parse_abstract_name(name).map(|name| SocketAddr::Unix(UnixSocketAddr::Abstract(name)))
+        }
+
+        if string == UNNAMED {
+            return Ok(SocketAddr::Unix(UnixSocketAddr::Unnamed))
         }
+
+        NamedSocketAddr::from_str(string).map(NamedSocketAddr::into)
     }
 }
 
-#[cfg(unix)]
-impl Debug for UnixSocketAddr {
-    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
-        match self {
-            UnixSocketAddr::AbstractOrUnnamed => write!(formatter, "(abstract or unnamed)"),
-            UnixSocketAddr::Pathname(path) => write!(formatter, "{path:?} (pathname)"),
+impl FromStr for BindableAddr {
+    type Err = ParseSocketAddrError;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        if let Some(name) = string.strip_prefix(ABSTRACT_PREFIX) {
+            return parse_abstract_name(name).map(BindableAddr::Abstract)
         }
+
+        NamedSocketAddr::from_str(string).map(BindableAddr::Named)
     }
 }
 
-impl Display for SocketAddr {
-    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
-        match self {
-            SocketAddr::Inet(inet_socket_addr) => write!(formatter, "{}", inet_socket_addr),
-            #[cfg(unix)]
-            SocketAddr::Unix(unix_socket_addr) => write!(formatter, "unix {:?}", unix_socket_addr),
+impl FromStr for NamedSocketAddr {
+    type Err = ParseSocketAddrError;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        if let Some(path) = string.strip_prefix(UNIX_SCHEME) {
+            if path.is_empty() {
+                return Err(ParseSocketAddrError::EmptyPath)
+            }
+
+            return parse_path(path).map(NamedSocketAddr::Unix)
+        }
+
+        #[cfg(windows)]
+        if string.starts_with(PIPE_PREFIX) {
+            return Ok(NamedSocketAddr::Pipe(PathBuf::from(string)))
         }
+
+        #[cfg(all(feature = "vsock", target_os = "linux"))]
+        if let Some(vsock_addr) = string.strip_prefix(VSOCK_SCHEME) {
+            let (cid, port) = vsock_addr.split_once(':').ok_or(ParseSocketAddrError::InvalidVsockAddr)?;
+
+            return Ok(NamedSocketAddr::Vsock {
+                cid: cid.parse().map_err(|_| ParseSocketAddrError::InvalidVsockAddr)?,
+                port: port.parse().map_err(|_| ParseSocketAddrError::InvalidVsockAddr)?,
+            })
+        }
+
+        if let Some(inet_socket_addr) = strip_tcp_scheme(string) {
+            return Ok(NamedSocketAddr::Inet(parse_inet(inet_socket_addr)?))
+        }
+
+        if UnixSocketAddr::is_pathname(string) {
+            return parse_path(string).map(NamedSocketAddr::Unix)
+        }
+
+        Ok(NamedSocketAddr::Inet(parse_inet(string)?))
     }
 }
 
-impl Display for NamedSocketAddr {
-    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
-        match self {
-            NamedSocketAddr::Inet(inet_socket_addr) => write!(formatter, "{}", inet_socket_addr),
-            #[cfg(unix)]
-            NamedSocketAddr::Unix(path) => write!(formatter, "unix {:?}", path),
+fn strip_tcp_scheme(string: &str) -> Option<&str> {
+    string.strip_prefix(TCP_SCHEME).or_else(|| string.strip_prefix(LEGACY_TCP_SCHEME))
+}
+
+// Reverses the escape_ascii used when displaying abstract names. Empty names and names
+// that don't fit into a sockaddr_un are rejected.
+fn parse_abstract_name(string: &str) -> Result<Vec<u8>, ParseSocketAddrError> {
+    let name = unescape(string).ok_or(ParseSocketAddrError::InvalidAbstractName)?;
+
+    if name.is_empty() || name.len() > MAX_ABSTRACT_NAME_LEN {
+        return Err(ParseSocketAddrError::InvalidAbstractName)
+    }
+
+    Ok(name)
+}
+
+// Reverses escape_path.
+fn parse_path(string: &str) -> Result<PathBuf, ParseSocketAddrError> {
+    let path = unescape(string).ok_or(ParseSocketAddrError::InvalidPath)?;
+
+    #[cfg(unix)]
+    return Ok(PathBuf::from(OsString::from_vec(path)));
+
+    #[cfg(not(unix))]
+    String::from_utf8(path).map(PathBuf::from).map_err(|_| ParseSocketAddrError::InvalidPath)
+}
+
+// Unescapes the sequences produced by escape_ascii, returning None for unknown ones.
+fn unescape(string: &str) -> Option<Vec<u8>> {
+    let hex_digit = |byte: u8| char::from(byte).to_digit(16).map(|digit| digit as u8);
+    let mut unescaped = Vec::with_capacity(string.len());
+    let mut bytes = string.bytes();
+
+    while let Some(byte) = bytes.next() {
+        if byte != b'\\' {
+            unescaped.push(byte);
+            continue
+        }
+
+        unescaped.push(match bytes.next()? {
+            b't' => b'\t',
+            b'r' => b'\r',
+            b'n' => b'\n',
+            byte @ (b'\\' | b'\'' | b'"') => byte,
+            b'x' => hex_digit(bytes.next()?)? << 4 | hex_digit(bytes.next()?)?,
+            _ => return None,
+        });
+    }
+
+    Some(unescaped)
+}
+
+// Unlike abstract names, paths are only escaped where needed so they stay readable:
+// Backslashes are doubled and bytes that aren't valid UTF-8 are written as \xNN.
+#[cfg(unix)]
+fn escape_path(path: &Path) -> String {
+    let mut escaped = String::new();
+
+    for chunk in path.as_os_str().as_bytes().utf8_chunks() {
+        escaped.push_str(&chunk.valid().replace('\\', "\\\\"));
+
+        for byte in chunk.invalid() {
+            write!(escaped, "\\x{:02x}", byte).unwrap();
+        }
+    }
+
+    escaped
+}
+
+// NOTE: Unix socket paths can't be bound to on other systems, they only need to
+//       display readably there.
+#[cfg(not(unix))]
+fn escape_path(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "\\\\")
+}
+
+// NOTE: In addition to numeric scope ids, which are handled by std, IPv6 addresses
+//       can be scoped by interface name like [fe80::1%eth0]:80 on unix systems.
+fn parse_inet(string: &str) -> Result<net::SocketAddr, AddrParseError> {
+    let error = match net::SocketAddr::from_str(string) {
+        Ok(inet_socket_addr) => return Ok(inet_socket_addr),
+        Err(error) => error,
+    };
+
+    #[cfg(unix)]
+    if let Some(inet_socket_addr) = parse_interface_scoped_inet(string) {
+        return Ok(inet_socket_addr)
+    }
+
+    Err(error)
+}
+
+#[cfg(unix)]
+fn parse_interface_scoped_inet(string: &str) -> Option<net::SocketAddr> {
+    let (host, port) = string.strip_prefix('[')?.split_once("]:")?;
+    let (ip, interface) = host.split_once('%')?;
+
+    let ip = Ipv6Addr::from_str(ip).ok()?;
+    let port = u16::from_str(port).ok()?;
+    let interface = CString::new(interface).ok()?;
+
+    match unsafe { libc::if_nametoindex(interface.as_ptr()) } {
+        0 => None,
+        scope_id => Some(net::SocketAddr::V6(net::SocketAddrV6::new(ip, port, 0, scope_id))),
+    }
+}
+
+// NOTE: Only names common filesystems, others are returned as their magic number in hex.
+#[cfg(target_os = "linux")]
+fn filesystem_type(path: &Path) -> io::Result<String> {
+    let path = CString::new(path.as_os_str().as_bytes()).map_err(|_| io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "Cannot inspect the filesystem of a path containing a null byte.",
+    ))?;
+
+    let mut statfs: libc::statfs = unsafe { mem::zeroed() };
+
+    if unsafe { libc::statfs(path.as_ptr(), &mut statfs) } < 0 {
+        return Err(io::Error::last_os_error())
+    }
+
+    let name = match statfs.f_type as u32 {
+        0x01021994 => "tmpfs",
+        0x858458f6 => "ramfs",
+        0xef53 => "ext4",
+        0x9123683e => "btrfs",
+        0x58465342 => "xfs",
+        0x2fc12fc1 => "zfs",
+        0xf2f52010 => "f2fs",
+        0x794c7630 => "overlayfs",
+        0x6969 => "nfs",
+        0xff534d42 => "cifs",
+        0x65735546 => "fuse",
+        magic => return Ok(format!("{:#x}", magic)),
+    };
+
+    Ok(name.to_string())
+}
+
+impl From<NamedSocketAddr> for SocketAddr {
+    fn from(named_socket_addr: NamedSocketAddr) -> Self {
+        named_socket_addr.to_socket_addr()
+    }
+}
+
+impl TryFrom<SocketAddr> for NamedSocketAddr {
+    type Error = io::Error;
+
+    fn try_from(socket_addr: SocketAddr) -> Result<Self, Self::Error> {
+        socket_addr.to_named_socket_addr()
+    }
+}
+
+impl TryFrom<SocketAddr> for BindableAddr {
+    type Error = io::Error;
+
+    fn try_from(socket_addr: SocketAddr) -> Result<Self, Self::Error> {
+        socket_addr.to_bindable()
+    }
+}
+
+impl From<NamedSocketAddr> for BindableAddr {
+    fn from(named_socket_addr: NamedSocketAddr) -> Self {
+        BindableAddr::Named(named_socket_addr)
+    }
+}
+
+impl From<BindableAddr> for SocketAddr {
+    fn from(bindable_addr: BindableAddr) -> Self {
+        match bindable_addr {
+            BindableAddr::Named(named_socket_addr) => named_socket_addr.to_socket_addr(),
+            BindableAddr::Abstract(name) => SocketAddr::Unix(UnixSocketAddr::Abstract(name)),
+        }
+    }
+}
+
+// NOTE: Abstract or unnamed unix addresses are never equal to a named address.
+impl PartialEq<NamedSocketAddr> for SocketAddr {
+    fn eq(&self, other: &NamedSocketAddr) -> bool {
+        match (self, other) {
+            (SocketAddr::Inet(inet_socket_addr), NamedSocketAddr::Inet(other)) => inet_socket_addr == other,
+            (SocketAddr::Unix(UnixSocketAddr::Pathname(path)), NamedSocketAddr::Unix(other)) => path == other,
+            #[cfg(windows)]
+            (SocketAddr::Pipe(pipe_name), NamedSocketAddr::Pipe(other)) => pipe_name == other,
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            (SocketAddr::Vsock { cid, port }, NamedSocketAddr::Vsock { cid: other_cid, port: other_port }) => cid == other_cid && port == other_port,
+            _ => false,
+        }
+    }
+}
+
+impl PartialEq<SocketAddr> for NamedSocketAddr {
+    fn eq(&self, other: &SocketAddr) -> bool {
+        other == self
+    }
+}
+
+impl From<net::SocketAddr> for SocketAddr {
+    fn from(inet_socket_addr: net::SocketAddr) -> SocketAddr {
+        SocketAddr::Inet(inet_socket_addr)
+    }
+}
+
+impl From<net::SocketAddr> for NamedSocketAddr {
+    fn from(inet_socket_addr: net::SocketAddr) -> NamedSocketAddr {
+        NamedSocketAddr::Inet(inet_socket_addr)
+    }
+}
+
+impl From<(IpAddr, u16)> for SocketAddr {
+    fn from((ip_addr, port): (IpAddr, u16)) -> SocketAddr {
+        SocketAddr::Inet(net::SocketAddr::from((ip_addr, port)))
+    }
+}
+
+impl From<(Ipv4Addr, u16)> for SocketAddr {
+    fn from((ipv4_addr, port): (Ipv4Addr, u16)) -> SocketAddr {
+        SocketAddr::Inet(net::SocketAddr::from((ipv4_addr, port)))
+    }
+}
+
+impl From<(Ipv6Addr, u16)> for SocketAddr {
+    fn from((ipv6_addr, port): (Ipv6Addr, u16)) -> SocketAddr {
+        SocketAddr::Inet(net::SocketAddr::from((ipv6_addr, port)))
+    }
+}
+
+impl From<(IpAddr, u16)> for NamedSocketAddr {
+    fn from((ip_addr, port): (IpAddr, u16)) -> NamedSocketAddr {
+        NamedSocketAddr::Inet(net::SocketAddr::from((ip_addr, port)))
+    }
+}
+
+impl From<(Ipv4Addr, u16)> for NamedSocketAddr {
+    fn from((ipv4_addr, port): (Ipv4Addr, u16)) -> NamedSocketAddr {
+        NamedSocketAddr::Inet(net::SocketAddr::from((ipv4_addr, port)))
+    }
+}
+
+impl From<(Ipv6Addr, u16)> for NamedSocketAddr {
+    fn from((ipv6_addr, port): (Ipv6Addr, u16)) -> NamedSocketAddr {
+        NamedSocketAddr::Inet(net::SocketAddr::from((ipv6_addr, port)))
+    }
+}
+
+impl TryFrom<&str> for SocketAddr {
+    type Error = ParseSocketAddrError;
+
+    fn try_from(string: &str) -> Result<Self, Self::Error> {
+        SocketAddr::from_str(string)
+    }
+}
+
+impl TryFrom<&str> for NamedSocketAddr {
+    type Error = ParseSocketAddrError;
+
+    fn try_from(string: &str) -> Result<Self, Self::Error> {
+        NamedSocketAddr::from_str(string)
+    }
+}
+
+impl From<PathBuf> for SocketAddr {
+    fn from(path: PathBuf) -> SocketAddr {
+        SocketAddr::Unix(UnixSocketAddr::Pathname(path))
+    }
+}
+
+impl From<PathBuf> for NamedSocketAddr {
+    fn from(path: PathBuf) -> NamedSocketAddr {
+        NamedSocketAddr::Unix(path)
+    }
+}
+
+impl From<&Path> for SocketAddr {
+    fn from(path: &Path) -> SocketAddr {
+        SocketAddr::unix(path)
+    }
+}
+
+impl From<&Path> for NamedSocketAddr {
+    fn from(path: &Path) -> NamedSocketAddr {
+        NamedSocketAddr::unix(path)
+    }
+}
+
+impl From<UnixSocketAddr> for SocketAddr {
+    fn from(unix_socket_addr: UnixSocketAddr) -> SocketAddr {
+        SocketAddr::Unix(unix_socket_addr)
+    }
+}
+
+#[cfg(all(feature = "net", unix))]
+impl From<unix::SocketAddr> for SocketAddr {
+    fn from(unix_socket_addr: unix::SocketAddr) -> Self {
+        SocketAddr::Unix(unix_socket_addr.into())
+    }
+}
+
+#[cfg(all(feature = "net", unix))]
+impl TryFrom<unix::SocketAddr> for NamedSocketAddr {
+    type Error = io::Error;
+
+    fn try_from(unix_socket_addr: unix::SocketAddr) -> Result<Self, Self::Error> {
+        SocketAddr::from(unix_socket_addr).to_named_socket_addr()
+    }
+}
+
+#[cfg(all(feature = "net", unix))]
+impl From<unix::SocketAddr> for UnixSocketAddr {
+    fn from(unix_socket_addr: unix::SocketAddr) -> Self {
+        if let Some(path) = unix_socket_addr.as_pathname() {
+            return UnixSocketAddr::Pathname(path.to_path_buf())
+        }
+
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        if let Some(name) = unix_socket_addr.as_abstract_name() {
+            return UnixSocketAddr::Abstract(name.to_vec())
+        }
+
+        UnixSocketAddr::Unnamed
+    }
+}
+
+impl Debug for UnixSocketAddr {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            UnixSocketAddr::Abstract(name) => write!(formatter, "\"{}\" (abstract)", name.escape_ascii()),
+            UnixSocketAddr::Unnamed => write!(formatter, "(unnamed)"),
+            UnixSocketAddr::Pathname(path) => write!(formatter, "{path:?} (pathname)"),
+        }
+    }
+}
+
+// NOTE: Display is meant to round trip through FromStr, use Debug for a more
+//       verbose representation. The alternate form ({:#}) always includes the
+//       scheme of TCP and Unix addresses.
+impl Display for UnixSocketAddr {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            UnixSocketAddr::Abstract(name) => write!(formatter, "{}{}", ABSTRACT_PREFIX, name.escape_ascii()),
+            UnixSocketAddr::Unnamed => write!(formatter, "{}", UNNAMED),
+            UnixSocketAddr::Pathname(path) => UnixSocketAddr::fmt_pathname(path, formatter),
+        }
+    }
+}
+
+impl Display for SocketAddr {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            SocketAddr::Inet(inet_socket_addr) => fmt_inet(inet_socket_addr, formatter),
+            SocketAddr::Unix(unix_socket_addr) => Display::fmt(unix_socket_addr, formatter),
+            #[cfg(windows)]
+            SocketAddr::Pipe(pipe_name) => write!(formatter, "{}", pipe_name.display()),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            SocketAddr::Vsock { cid, port } => write!(formatter, "{}{}:{}", VSOCK_SCHEME, cid, port),
+        }
+    }
+}
+
+fn fmt_inet(inet_socket_addr: &net::SocketAddr, formatter: &mut Formatter) -> fmt::Result {
+    if formatter.alternate() {
+        write!(formatter, "{}", TCP_SCHEME)?;
+    }
+
+    #[cfg(unix)]
+    if let Some(interface) = scope_interface(inet_socket_addr) {
+        return write!(formatter, "[{}%{}]:{}", inet_socket_addr.ip(), interface, inet_socket_addr.port())
+    }
+
+    write!(formatter, "{}", inet_socket_addr)
+}
+
+// NOTE: The reverse of parse_interface_scoped_inet, scope ids without an interface (e.g.
+//       because it was removed since) keep their numeric form.
+#[cfg(unix)]
+fn scope_interface(inet_socket_addr: &net::SocketAddr) -> Option<String> {
+    let scope_id = match inet_socket_addr {
+        net::SocketAddr::V6(inet_socket_addr) if inet_socket_addr.scope_id() != 0 => inet_socket_addr.scope_id(),
+        _ => return None,
+    };
+
+    let mut name = [0 as libc::c_char; libc::IF_NAMESIZE];
+
+    if unsafe { libc::if_indextoname(scope_id, name.as_mut_ptr()) }.is_null() {
+        return None
+    }
+
+    let name = unsafe { CStr::from_ptr(name.as_ptr()) };
+
+    name.to_str().ok().map(str::to_owned)
+}
+
+impl Display for BindableAddr {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            BindableAddr::Named(named_socket_addr) => Display::fmt(named_socket_addr, formatter),
+            BindableAddr::Abstract(name) => write!(formatter, "{}{}", ABSTRACT_PREFIX, name.escape_ascii()),
+        }
+    }
+}
+
+impl Display for NamedSocketAddr {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            NamedSocketAddr::Inet(inet_socket_addr) => fmt_inet(inet_socket_addr, formatter),
+            NamedSocketAddr::Unix(path) => UnixSocketAddr::fmt_pathname(path, formatter),
+            #[cfg(windows)]
+            NamedSocketAddr::Pipe(pipe_name) => write!(formatter, "{}", pipe_name.display()),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            NamedSocketAddr::Vsock { cid, port } => write!(formatter, "{}{}:{}", VSOCK_SCHEME, cid, port),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    #[cfg(unix)]
+    use std::ffi::OsStr;
+
+    #[cfg(unix)]
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_as_string() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct Config {
+            #[serde(serialize_with = "SocketAddr::serialize_as_str", deserialize_with = "SocketAddr::deserialize_from_str")]
+            listen: SocketAddr,
+            #[serde(serialize_with = "NamedSocketAddr::serialize_as_option_str", deserialize_with = "NamedSocketAddr::deserialize_from_option_str")]
+            upstream: Option<NamedSocketAddr>,
+        }
+
+        let config = Config {
+            listen: SocketAddr::Inet("127.0.0.1:8080".parse().unwrap()),
+            upstream: Some(NamedSocketAddr::unix("/run/app.sock")),
+        };
+
+        let json = serde_json::to_string(&config).unwrap();
+
+        assert_eq!(json, r#"{"listen":"127.0.0.1:8080","upstream":"/run/app.sock"}"#);
+        assert_eq!(serde_json::from_str::<Config>(&json).unwrap(), config);
+
+        let config = serde_json::from_str::<Config>(r#"{"listen":"unix:app.sock","upstream":null}"#).unwrap();
+
+        assert_eq!(config.listen, SocketAddr::unix("app.sock"));
+        assert_eq!(config.upstream, None);
+        assert!(serde_json::from_str::<Config>(r#"{"listen":"not an address","upstream":null}"#).is_err());
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let named_socket_addrs = [
+            NamedSocketAddr::Inet("127.0.0.1:8080".parse().unwrap()),
+            NamedSocketAddr::Inet("[::1]:443".parse().unwrap()),
+            NamedSocketAddr::unix("/run/app.sock"),
+            NamedSocketAddr::unix("./app.sock"),
+            NamedSocketAddr::unix("app.sock"),
+            NamedSocketAddr::unix("127.0.0.1:8080"),
+        ];
+
+        for named_socket_addr in named_socket_addrs {
+            assert_eq!(named_socket_addr.to_string().parse::<NamedSocketAddr>().unwrap(), named_socket_addr);
+            assert_eq!(format!("{:#}", named_socket_addr).parse::<NamedSocketAddr>().unwrap(), named_socket_addr);
+
+            let socket_addr = SocketAddr::from(named_socket_addr);
+
+            assert_eq!(socket_addr.to_string().parse::<SocketAddr>().unwrap(), socket_addr);
+            assert_eq!(format!("{:#}", socket_addr).parse::<SocketAddr>().unwrap(), socket_addr);
+        }
+    }
+
+    #[cfg(feature = "net")]
+    #[tokio::test]
+    async fn resolve_hostnames() {
+        let socket_addrs = SocketAddr::resolve("localhost:8080").await.unwrap();
+
+        assert!(!socket_addrs.is_empty());
+        assert!(socket_addrs.iter().all(|socket_addr| socket_addr.port() == Some(8080) && socket_addr.is_inet()));
+
+        assert_eq!(
+            SocketAddr::resolve("tcp://127.0.0.1:80").await.unwrap(),
+            vec![SocketAddr::Inet("127.0.0.1:80".parse().unwrap())],
+        );
+        assert_eq!(SocketAddr::resolve("/run/app.sock").await.unwrap(), vec![SocketAddr::unix("/run/app.sock")]);
+        assert_eq!(SocketAddr::resolve("unix:app.sock").await.unwrap(), vec![SocketAddr::unix("app.sock")]);
+        assert!(SocketAddr::resolve("localhost").await.is_err());
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(matches!("not an address".parse::<NamedSocketAddr>(), Err(ParseSocketAddrError::Inet(_))));
+        assert_eq!("unix:".parse::<NamedSocketAddr>(), Err(ParseSocketAddrError::EmptyPath));
+        assert_eq!("@".parse::<SocketAddr>(), Err(ParseSocketAddrError::InvalidAbstractName));
+        assert_eq!("@bad\\q".parse::<SocketAddr>(), Err(ParseSocketAddrError::InvalidAbstractName));
+        assert_eq!("@\\x4".parse::<SocketAddr>(), Err(ParseSocketAddrError::InvalidAbstractName));
+        assert_eq!(format!("@{}", "a".repeat(108)).parse::<SocketAddr>(), Err(ParseSocketAddrError::InvalidAbstractName));
+        assert!(format!("@{}", "a".repeat(107)).parse::<SocketAddr>().is_ok());
+
+        let error = "not an address".parse::<NamedSocketAddr>().unwrap_err();
+
+        assert!(error::Error::source(&error).is_some());
+    }
+
+    #[test]
+    fn tuple_and_str_conversions() {
+        let inet_socket_addr: net::SocketAddr = "127.0.0.1:80".parse().unwrap();
+
+        assert_eq!(SocketAddr::from((IpAddr::V4(Ipv4Addr::LOCALHOST), 80)), SocketAddr::Inet(inet_socket_addr));
+        assert_eq!(SocketAddr::from((Ipv4Addr::LOCALHOST, 80)), SocketAddr::Inet(inet_socket_addr));
+        assert_eq!(NamedSocketAddr::from((Ipv4Addr::LOCALHOST, 80)), NamedSocketAddr::Inet(inet_socket_addr));
+        assert_eq!(
+            NamedSocketAddr::from((Ipv6Addr::LOCALHOST, 443)),
+            NamedSocketAddr::Inet("[::1]:443".parse().unwrap()),
+        );
+
+        assert_eq!(NamedSocketAddr::try_from("127.0.0.1:80"), Ok(NamedSocketAddr::Inet(inet_socket_addr)));
+        assert_eq!(SocketAddr::try_from("/run/app.sock"), Ok(SocketAddr::unix("/run/app.sock")));
+        assert!(NamedSocketAddr::try_from("app.sock").is_err());
+    }
+
+    #[test]
+    fn predicates_and_accessors() {
+        let inet = SocketAddr::Inet("127.0.0.1:80".parse().unwrap());
+        let unix = SocketAddr::unix("/run/app.sock");
+
+        assert!(inet.is_inet() && !inet.is_unix());
+        assert!(unix.is_unix() && !unix.is_inet());
+        assert_eq!(inet.port(), Some(80));
+        assert_eq!(inet.ip(), Some(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+        assert_eq!(unix.port(), None);
+        assert_eq!(unix.as_inet(), None);
+        assert_eq!(unix.as_unix(), Some(&UnixSocketAddr::Pathname(PathBuf::from("/run/app.sock"))));
+        assert_eq!(inet.as_unix(), None);
+        assert_eq!(inet.map_inet(|inet_socket_addr| net::SocketAddr::new(inet_socket_addr.ip(), 81)).port(), Some(81));
+    }
+
+    #[test]
+    fn unix_scheme() {
+        assert_eq!("unix:relative/app.sock".parse::<NamedSocketAddr>(), Ok(NamedSocketAddr::unix("relative/app.sock")));
+        assert_eq!("unix:/run/app.sock".parse::<NamedSocketAddr>(), Ok(NamedSocketAddr::unix("/run/app.sock")));
+        assert_eq!("unix:127.0.0.1:80".parse::<NamedSocketAddr>(), Ok(NamedSocketAddr::unix("127.0.0.1:80")));
+        assert_eq!("unix:app.sock".parse::<SocketAddr>(), Ok(SocketAddr::unix("app.sock")));
+
+        assert_eq!(NamedSocketAddr::unix("relative/app.sock").to_string(), "unix:relative/app.sock");
+        assert_eq!(NamedSocketAddr::unix("/run/app.sock").to_string(), "/run/app.sock");
+        assert_eq!(format!("{:#}", NamedSocketAddr::unix("/run/app.sock")), "unix:/run/app.sock");
+    }
+
+    #[test]
+    fn tcp_scheme() {
+        assert_eq!("tcp:127.0.0.1:80".parse::<NamedSocketAddr>(), Ok(NamedSocketAddr::Inet("127.0.0.1:80".parse().unwrap())));
+        assert!(matches!("tcp:app.sock".parse::<NamedSocketAddr>(), Err(ParseSocketAddrError::Inet(_))));
+    }
+
+    #[cfg(feature = "net")]
+    #[tokio::test]
+    async fn resolve_schemes() {
+        assert_eq!(SocketAddr::resolve("tcp:127.0.0.1:80").await.unwrap(), vec![SocketAddr::Inet("127.0.0.1:80".parse().unwrap())]);
+        #[cfg(unix)]
+        assert_eq!(
+            SocketAddr::resolve("unix:app.sock").await.unwrap(),
+            vec![SocketAddr::Unix(UnixSocketAddr::Pathname(PathBuf::from("app.sock")))],
+        );
+        #[cfg(unix)]
+        assert_eq!(SocketAddr::resolve("unix:").await.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    }
+
+    // NOTE: Matches every unix variant without cfg attributes, this has to compile on all platforms.
+    #[test]
+    fn unix_variants_everywhere() {
+        fn describe(named_socket_addr: &NamedSocketAddr) -> &'static str {
+            #[allow(unreachable_patterns)]
+            match named_socket_addr {
+                NamedSocketAddr::Inet(_) => "inet",
+                NamedSocketAddr::Unix(_) => "unix",
+                _ => "other",
+            }
+        }
+
+        assert_eq!(describe(&"127.0.0.1:80".parse().unwrap()), "inet");
+        assert_eq!(describe(&"unix:/run/app.sock".parse().unwrap()), "unix");
+
+        let socket_addr = SocketAddr::from(NamedSocketAddr::unix("/run/app.sock"));
+
+        assert!(matches!(socket_addr, SocketAddr::Unix(UnixSocketAddr::Pathname(_))));
+        assert!(!matches!(UnixSocketAddr::Unnamed, UnixSocketAddr::Pathname(_)));
+    }
+
+    #[test]
+    fn canonicalize() {
+        let temp_dir = std::env::temp_dir();
+        let named_socket_addr = NamedSocketAddr::unix(temp_dir.join(".").join("."));
+
+        assert_eq!(named_socket_addr.canonicalize().unwrap(), NamedSocketAddr::Unix(temp_dir.canonicalize().unwrap()));
+
+        let missing = NamedSocketAddr::unix(temp_dir.join("tokio-unix-tcp-missing").join("app.sock"));
+
+        assert_eq!(missing.canonicalize().unwrap_err().kind(), io::ErrorKind::NotFound);
+
+        let inet_socket_addr = NamedSocketAddr::Inet("127.0.0.1:80".parse().unwrap());
+
+        assert_eq!(inet_socket_addr.canonicalize().unwrap(), inet_socket_addr);
+    }
+
+    #[cfg(all(feature = "net", unix))]
+    #[tokio::test]
+    async fn is_stale_unix_socket() {
+        use crate::{test_util, Listener};
+
+        let path = test_util::temp_path("stale.sock");
+        let named_socket_addr = NamedSocketAddr::unix(&path);
+
+        assert!(!named_socket_addr.is_stale_unix_socket().await.unwrap());
+
+        let listener = Listener::bind(&named_socket_addr).await.unwrap();
+
+        assert!(!named_socket_addr.is_stale_unix_socket().await.unwrap());
+
+        drop(listener);
+        assert!(named_socket_addr.is_stale_unix_socket().await.unwrap());
+
+        // NOTE: Files that aren't sockets are never considered stale.
+        fs::remove_file(&path).unwrap();
+        fs::write(&path, b"").unwrap();
+        assert!(!named_socket_addr.is_stale_unix_socket().await.unwrap());
+        fs::remove_file(&path).unwrap();
+
+        assert!(!NamedSocketAddr::Inet("127.0.0.1:80".parse().unwrap()).is_stale_unix_socket().await.unwrap());
+    }
+
+    #[test]
+    fn unix_name_accessors() {
+        let pathname = UnixSocketAddr::Pathname(PathBuf::from("/run/app.sock"));
+        let abstract_name = UnixSocketAddr::Abstract(b"app".to_vec());
+
+        assert_eq!(pathname.as_pathname(), Some(Path::new("/run/app.sock")));
+        assert_eq!(pathname.as_abstract_name(), None);
+        assert_eq!(abstract_name.as_abstract_name(), Some(&b"app"[..]));
+        assert_eq!(abstract_name.as_pathname(), None);
+        assert_eq!(UnixSocketAddr::Unnamed.as_pathname(), None);
+        assert_eq!(UnixSocketAddr::Unnamed.as_abstract_name(), None);
+    }
+
+    #[test]
+    fn compare_across_types() {
+        let named_socket_addr = NamedSocketAddr::unix("/run/app.sock");
+
+        assert_eq!(SocketAddr::unix("/run/app.sock"), named_socket_addr);
+        assert_eq!(named_socket_addr, SocketAddr::unix("/run/app.sock"));
+        assert_ne!(SocketAddr::unix("/run/other.sock"), named_socket_addr);
+        assert_ne!(SocketAddr::Unix(UnixSocketAddr::Unnamed), named_socket_addr);
+        assert_ne!(SocketAddr::Unix(UnixSocketAddr::Abstract(b"/run/app.sock".to_vec())), named_socket_addr);
+
+        let inet_socket_addr: net::SocketAddr = "127.0.0.1:80".parse().unwrap();
+
+        assert_eq!(SocketAddr::Inet(inet_socket_addr), NamedSocketAddr::Inet(inet_socket_addr));
+        assert_ne!(SocketAddr::Inet(inet_socket_addr), named_socket_addr);
+    }
+
+    #[cfg(all(feature = "net", unix))]
+    #[test]
+    fn to_tokio_round_trips() {
+        let pathname = UnixSocketAddr::Pathname(PathBuf::from("/run/app.sock"));
+
+        assert_eq!(UnixSocketAddr::from(pathname.to_tokio().unwrap()), pathname);
+
+        #[cfg(target_os = "linux")]
+        {
+            let abstract_name = UnixSocketAddr::Abstract(b"app\0name".to_vec());
+
+            assert_eq!(UnixSocketAddr::from(abstract_name.to_tokio().unwrap()), abstract_name);
+        }
+
+        assert_eq!(UnixSocketAddr::Unnamed.to_tokio().unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn numeric_scope_ids() {
+        let named_socket_addr = "[fe80::1%4294967295]:80".parse::<NamedSocketAddr>().unwrap();
+
+        assert_eq!(named_socket_addr, NamedSocketAddr::Inet(net::SocketAddrV6::new("fe80::1".parse().unwrap(), 80, 0, u32::MAX).into()));
+
+        // NOTE: No interface has this index, so the id is displayed as a number.
+        assert_eq!(named_socket_addr.to_string(), "[fe80::1%4294967295]:80");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn interface_scope_ids() {
+        let interface = if cfg!(target_os = "linux") { "lo" } else { "lo0" };
+        let index = unsafe { libc::if_nametoindex(CString::new(interface).unwrap().as_ptr()) };
+
+        assert_ne!(index, 0);
+
+        let named_socket_addr = format!("[fe80::1%{}]:80", interface).parse::<NamedSocketAddr>().unwrap();
+
+        assert_eq!(named_socket_addr, NamedSocketAddr::Inet(net::SocketAddrV6::new("fe80::1".parse().unwrap(), 80, 0, index).into()));
+        assert_eq!(named_socket_addr.to_string(), format!("[fe80::1%{}]:80", interface));
+        assert_eq!(format!("[fe80::1%{}]:80", index).parse::<NamedSocketAddr>().unwrap(), named_socket_addr);
+
+        assert!("[fe80::1%nonexistent0]:80".parse::<NamedSocketAddr>().is_err());
+    }
+
+    #[test]
+    fn path_conversions() {
+        let path = Path::new("/run/app.sock");
+
+        assert_eq!(NamedSocketAddr::from(path), NamedSocketAddr::Unix(path.to_path_buf()));
+        assert_eq!(NamedSocketAddr::from(path), NamedSocketAddr::from(path.to_path_buf()));
+        assert_eq!(SocketAddr::from(path), SocketAddr::Unix(UnixSocketAddr::Pathname(path.to_path_buf())));
+        assert_eq!(SocketAddr::unix("app.sock"), SocketAddr::from(NamedSocketAddr::unix("app.sock")));
+        assert!(SocketAddr::unix("127.0.0.1:8080").is_unix());
+    }
+
+    // NOTE: Runs with and without the net flag, address types are usable in configuration
+    //       crates that don't depend on tokio.
+    #[test]
+    fn config_without_runtime() {
+        let named_socket_addrs: BTreeSet<NamedSocketAddr> = ["/run/b.sock", "127.0.0.1:80", "/run/a.sock", "127.0.0.1:80"]
+            .into_iter()
+            .map(|input| input.parse().unwrap())
+            .collect();
+
+        assert_eq!(
+            named_socket_addrs.into_iter().map(SocketAddr::from).collect::<Vec<_>>(),
+            [SocketAddr::Inet("127.0.0.1:80".parse().unwrap()), SocketAddr::unix("/run/a.sock"), SocketAddr::unix("/run/b.sock")],
+        );
+    }
+
+    #[test]
+    fn to_bindable() {
+        let abstract_socket_addr = SocketAddr::Unix(UnixSocketAddr::Abstract(b"app".to_vec()));
+
+        // NOTE: Abstract names are only bindable when asked for explicitly.
+        assert!(NamedSocketAddr::try_from(abstract_socket_addr.clone()).is_err());
+        assert_eq!(BindableAddr::try_from(abstract_socket_addr.clone()).unwrap(), BindableAddr::Abstract(b"app".to_vec()));
+        assert_eq!(SocketAddr::from(abstract_socket_addr.clone().to_bindable().unwrap()), abstract_socket_addr);
+
+        assert_eq!(
+            SocketAddr::unix("/run/app.sock").to_bindable().unwrap(),
+            BindableAddr::from(NamedSocketAddr::unix("/run/app.sock")),
+        );
+        assert_eq!(SocketAddr::Unix(UnixSocketAddr::Unnamed).to_bindable().unwrap_err().kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn to_sockaddr_bytes() {
+        use std::{fs, os::unix::fs::FileTypeExt};
+
+        let family_length = mem::offset_of!(libc::sockaddr_un, sun_path) as libc::socklen_t;
+        let path = std::env::temp_dir().join(format!("tokio-unix-tcp-{}-sockaddr.sock", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let (sockaddr, length) = UnixSocketAddr::Pathname(path.clone()).to_sockaddr_bytes().unwrap();
+
+        assert_eq!(length, family_length + path.as_os_str().len() as libc::socklen_t + 1);
+
+        // NOTE: The kernel has to accept the address, binding creates the socket file.
+        unsafe {
+            let fd = libc::socket(libc::AF_UNIX, libc::SOCK_STREAM, 0);
+
+            assert!(fd >= 0);
+            assert_eq!(libc::bind(fd, &sockaddr as *const libc::sockaddr_un as *const libc::sockaddr, length), 0);
+            libc::close(fd);
+        }
+
+        assert!(fs::metadata(&path).unwrap().file_type().is_socket());
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(UnixSocketAddr::Unnamed.to_sockaddr_bytes().unwrap().1, family_length);
+        assert_eq!(UnixSocketAddr::Pathname(PathBuf::from("a\0b")).to_sockaddr_bytes().unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(UnixSocketAddr::Pathname(PathBuf::from("a".repeat(108))).to_sockaddr_bytes().unwrap_err().kind(), io::ErrorKind::InvalidInput);
+
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            let (sockaddr, length) = UnixSocketAddr::Abstract(b"app".to_vec()).to_sockaddr_bytes().unwrap();
+
+            assert_eq!(length, family_length + 4);
+            assert_eq!(&sockaddr.sun_path[..4], [0, b'a' as libc::c_char, b'p' as libc::c_char, b'p' as libc::c_char]);
+        }
+    }
+
+    #[test]
+    fn alternate_display() {
+        let inet_socket_addr: net::SocketAddr = "[::1]:443".parse().unwrap();
+
+        assert_eq!(format!("{:#}", SocketAddr::Inet(inet_socket_addr)), "tcp://[::1]:443");
+        assert_eq!(format!("{:#}", NamedSocketAddr::Inet(inet_socket_addr)), "tcp://[::1]:443");
+        assert_eq!(format!("{:#}", BindableAddr::Named(NamedSocketAddr::Inet(inet_socket_addr))), "tcp://[::1]:443");
+        assert_eq!(format!("{:#}", SocketAddr::unix("/run/app.sock")), "unix:/run/app.sock");
+        assert_eq!(format!("{:#}", UnixSocketAddr::Pathname(PathBuf::from("/run/app.sock"))), "unix:/run/app.sock");
+
+        // NOTE: Abstract names and unnamed addresses look the same either way.
+        assert_eq!(format!("{:#}", SocketAddr::Unix(UnixSocketAddr::Abstract(b"app".to_vec()))), "@app");
+        assert_eq!(format!("{:#}", BindableAddr::Abstract(b"app".to_vec())), "@app");
+        assert_eq!(format!("{:#}", SocketAddr::Unix(UnixSocketAddr::Unnamed)), SocketAddr::Unix(UnixSocketAddr::Unnamed).to_string());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_with_modules() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct Config {
+            #[serde(with = "crate::socket_addr::as_str")]
+            listen: SocketAddr,
+            #[serde(with = "crate::socket_addr::as_opt_str")]
+            admin: Option<SocketAddr>,
+            #[serde(with = "crate::socket_addr::named_as_str")]
+            upstream: NamedSocketAddr,
+            #[serde(with = "crate::socket_addr::named_as_opt_str")]
+            fallback: Option<NamedSocketAddr>,
+        }
+
+        let config = Config {
+            listen: SocketAddr::Unix(UnixSocketAddr::Abstract(b"app".to_vec())),
+            admin: None,
+            upstream: NamedSocketAddr::Inet("127.0.0.1:8080".parse().unwrap()),
+            fallback: Some(NamedSocketAddr::unix("app.sock")),
+        };
+
+        let json = serde_json::to_string(&config).unwrap();
+
+        assert_eq!(json, r#"{"listen":"@app","admin":null,"upstream":"127.0.0.1:8080","fallback":"unix:app.sock"}"#);
+        assert_eq!(serde_json::from_str::<Config>(&json).unwrap(), config);
+        assert!(serde_json::from_str::<Config>(&json.replace("127.0.0.1:8080", "@app")).is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn socket_path_filesystem() {
+        // NOTE: procfs isn't named, its magic number is returned instead. Missing paths
+        //       are looked up through their parent directory.
+        assert_eq!(NamedSocketAddr::unix("/proc/self").socket_path_filesystem().unwrap(), Some("0x9fa0".to_string()));
+        assert_eq!(NamedSocketAddr::unix("/proc/missing.sock").socket_path_filesystem().unwrap(), Some("0x9fa0".to_string()));
+
+        if Path::new("/dev/shm").exists() {
+            assert_eq!(NamedSocketAddr::unix("/dev/shm/app.sock").socket_path_filesystem().unwrap(), Some("tmpfs".to_string()));
+        }
+
+        assert!(NamedSocketAddr::unix("app.sock").socket_path_filesystem().unwrap().is_some());
+        assert!(NamedSocketAddr::unix("/missing/dir/app.sock").socket_path_filesystem().is_err());
+        assert_eq!(NamedSocketAddr::Inet("127.0.0.1:80".parse().unwrap()).socket_path_filesystem().unwrap(), None);
+    }
+
+    #[cfg(all(feature = "net", unix))]
+    #[test]
+    fn named_from_tokio() {
+        let pathname = UnixSocketAddr::Pathname(PathBuf::from("/run/app.sock"));
+
+        assert_eq!(NamedSocketAddr::try_from(pathname.to_tokio().unwrap()).unwrap(), NamedSocketAddr::unix("/run/app.sock"));
+
+        // NOTE: Tokio reports unbound peers using an unnamed address, which has no name to connect to.
+        let (a, _b) = std::os::unix::net::UnixStream::pair().unwrap();
+        let unnamed = unix::SocketAddr::from(a.local_addr().unwrap());
+
+        assert!(NamedSocketAddr::try_from(unnamed).is_err());
+
+        #[cfg(target_os = "linux")]
+        assert!(NamedSocketAddr::try_from(UnixSocketAddr::Abstract(b"app".to_vec()).to_tokio().unwrap()).is_err());
+    }
+
+    #[test]
+    fn logging_keys() {
+        let inet = SocketAddr::Inet("127.0.0.1:51234".parse().unwrap());
+        let mapped = SocketAddr::Inet("[::ffff:127.0.0.1]:51235".parse().unwrap());
+
+        assert_eq!(inet.logging_key(), "127.0.0.1");
+        assert_eq!(mapped.logging_key(), inet.logging_key());
+        assert_eq!(inet.logging_key_with_port(), "127.0.0.1:51234");
+        assert_eq!(mapped.logging_key_with_port(), "127.0.0.1:51235");
+        assert_eq!(SocketAddr::Inet("[::1]:80".parse().unwrap()).logging_key_with_port(), "[::1]:80");
+
+        assert_eq!(SocketAddr::unix("/run/app.sock").logging_key(), "/run/app.sock");
+        assert_eq!(SocketAddr::Unix(UnixSocketAddr::Abstract(b"a".to_vec())).logging_key(), "(unix-local)");
+        assert_eq!(SocketAddr::Unix(UnixSocketAddr::Unnamed).logging_key_with_port(), "(unix-local)");
+    }
+
+    #[test]
+    fn abstract_and_unnamed_round_trip() {
+        let socket_addrs = [
+            SocketAddr::Unix(UnixSocketAddr::Abstract(b"app".to_vec())),
+            SocketAddr::Unix(UnixSocketAddr::Abstract(b"with space\0and\tescapes\\\xff".to_vec())),
+            SocketAddr::Unix(UnixSocketAddr::Unnamed),
+        ];
+
+        for socket_addr in socket_addrs {
+            assert_eq!(socket_addr.to_string().parse::<SocketAddr>().unwrap(), socket_addr);
+        }
+
+        assert_eq!(SocketAddr::Unix(UnixSocketAddr::Abstract(b"app".to_vec())).to_string(), "@app");
+        assert_eq!(SocketAddr::Unix(UnixSocketAddr::Unnamed).to_string(), "(unnamed)");
+    }
+
+    #[test]
+    fn bindable_round_trips() {
+        let bindable_addrs = [
+            BindableAddr::Abstract(b"app\x01".to_vec()),
+            BindableAddr::Named(NamedSocketAddr::unix("/run/app.sock")),
+            BindableAddr::Named(NamedSocketAddr::Inet("0.0.0.0:80".parse().unwrap())),
+        ];
+
+        for bindable_addr in bindable_addrs {
+            assert_eq!(bindable_addr.to_string().parse::<BindableAddr>().unwrap(), bindable_addr);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn pathname_escapes() {
+        let path = NamedSocketAddr::unix(OsStr::from_bytes(b"/run/a\\b\xff.sock"));
+
+        assert_eq!(path.to_string(), r"/run/a\\b\xff.sock");
+        assert_eq!(r"unix:a\x41".parse::<NamedSocketAddr>().unwrap(), NamedSocketAddr::unix("aA"));
+        assert_eq!(r"/run/a\q".parse::<NamedSocketAddr>(), Err(ParseSocketAddrError::InvalidPath));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn inherited_fd() {
+        assert_eq!(NamedSocketAddr::from_fd(3).inherited_fd(), Some(3));
+        assert_eq!(NamedSocketAddr::from_fd(3).to_string(), "/proc/self/fd/3");
+        assert_eq!(NamedSocketAddr::unix("/dev/fd/4").inherited_fd(), Some(4));
+        assert_eq!(NamedSocketAddr::unix("/dev/fd/stdin").inherited_fd(), None);
+        assert_eq!(NamedSocketAddr::unix("/run/app.sock").inherited_fd(), None);
+        assert_eq!(NamedSocketAddr::Inet("127.0.0.1:80".parse().unwrap()).inherited_fd(), None);
+    }
+
+    #[cfg(unix)]
+    fn inet_socket_addr() -> impl Strategy<Value = net::SocketAddr> {
+        prop_oneof![
+            any::<(Ipv4Addr, u16)>().prop_map(net::SocketAddr::from),
+            // NOTE: The flow info isn't part of the displayed form.
+            any::<(Ipv6Addr, u16, u32)>().prop_map(|(ip, port, scope_id)| net::SocketAddrV6::new(ip, port, 0, scope_id).into()),
+        ]
+    }
+
+    // NOTE: Covers relative paths that look like other kinds of addresses, backslashes
+    //       and bytes that aren't valid UTF-8.
+    #[cfg(unix)]
+    fn path() -> impl Strategy<Value = PathBuf> {
+        let prefix = prop_oneof![Just("/"), Just("./"), Just(""), Just("unix:"), Just("@"), Just("tcp://"), Just("\\"), Just("(unnamed)")];
+
+        (prefix, prop::collection::vec(any::<u8>(), 1..32)).prop_map(|(prefix, bytes)| {
+            PathBuf::from(OsString::from_vec([prefix.as_bytes(), &bytes].concat()))
+        })
+    }
+
+    #[cfg(unix)]
+    fn unix_socket_addr() -> impl Strategy<Value = UnixSocketAddr> {
+        prop_oneof![
+            path().prop_map(UnixSocketAddr::Pathname),
+            prop::collection::vec(any::<u8>(), 1..=MAX_ABSTRACT_NAME_LEN).prop_map(UnixSocketAddr::Abstract),
+            Just(UnixSocketAddr::Unnamed),
+        ]
+    }
+
+    #[cfg(unix)]
+    proptest! {
+        #[test]
+        fn display_round_trips_for_any_address(socket_addr in prop_oneof![
+            inet_socket_addr().prop_map(SocketAddr::Inet),
+            unix_socket_addr().prop_map(SocketAddr::Unix),
+        ]) {
+            prop_assert_eq!(socket_addr.to_string().parse::<SocketAddr>().unwrap(), socket_addr.clone());
+            prop_assert_eq!(format!("{:#}", socket_addr).parse::<SocketAddr>().unwrap(), socket_addr.clone());
+
+            if let Ok(bindable_addr) = socket_addr.clone().to_bindable() {
+                prop_assert_eq!(bindable_addr.to_string().parse::<BindableAddr>().unwrap(), bindable_addr);
+            }
+
+            if let Ok(named_socket_addr) = socket_addr.to_named_socket_addr() {
+                prop_assert_eq!(named_socket_addr.to_string().parse::<NamedSocketAddr>().unwrap(), named_socket_addr.clone());
+                prop_assert_eq!(format!("{:#}", named_socket_addr).parse::<NamedSocketAddr>().unwrap(), named_socket_addr);
+            }
         }
     }
 }
@@ -0,0 +1,79 @@
+/*
+ * Copyright (c) 2023, networkException <git@nwex.de>
+ *
+ * SPDX-License-Identifier: BSD-2-Clause OR MIT
+ */
+
+use std::fmt;
+
+use socket2::Type;
+
+#[cfg(windows)]
+const SOCK_SEQPACKET: i32 = 5;
+#[cfg(unix)]
+const SOCK_SEQPACKET: i32 = libc::SOCK_SEQPACKET;
+
+// NOTE: Stream and Listener currently assume SOCK_STREAM sockets. The type of an
+//       adopted socket can be checked using Stream::socket_type to detect sockets
+//       created with a different type, which aren't handled correctly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SocketType {
+    Stream,
+    Datagram,
+    SeqPacket,
+    Other(i32),
+}
+
+impl From<Type> for SocketType {
+    fn from(socket_type: Type) -> SocketType {
+        if socket_type == Type::STREAM {
+            SocketType::Stream
+        } else if socket_type == Type::DGRAM {
+            SocketType::Datagram
+        } else if i32::from(socket_type) == SOCK_SEQPACKET {
+            SocketType::SeqPacket
+        } else {
+            SocketType::Other(socket_type.into())
+        }
+    }
+}
+
+impl fmt::Display for SocketType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SocketType::Stream => f.write_str("stream"),
+            SocketType::Datagram => f.write_str("datagram"),
+            SocketType::SeqPacket => f.write_str("seqpacket"),
+            SocketType::Other(socket_type) => write!(f, "{}", socket_type),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    #[test]
+    fn from_type() {
+        assert_eq!(SocketType::from(Type::STREAM), SocketType::Stream);
+        assert_eq!(SocketType::from(Type::DGRAM), SocketType::Datagram);
+        assert_eq!(SocketType::from(Type::from(SOCK_SEQPACKET)), SocketType::SeqPacket);
+        assert_eq!(SocketType::from(Type::from(3)), SocketType::Other(3));
+
+        assert_eq!(SocketType::Stream.to_string(), "stream");
+        assert_eq!(SocketType::SeqPacket.to_string(), "seqpacket");
+        assert_eq!(SocketType::Other(42).to_string(), "42");
+    }
+
+    #[tokio::test]
+    async fn stream_socket_type() {
+        let (client, server) = test_util::tcp_pair().await;
+
+        assert_eq!(client.socket_type().unwrap(), SocketType::Stream);
+        assert_eq!(server.socket_type().unwrap(), SocketType::Stream);
+
+        #[cfg(unix)]
+        assert_eq!(test_util::unix_pair().0.socket_type().unwrap(), SocketType::Stream);
+    }
+}
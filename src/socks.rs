@@ -0,0 +1,298 @@
+/*
+ * Copyright (c) 2023, networkException <git@nwex.de>
+ *
+ * SPDX-License-Identifier: BSD-2-Clause OR MIT
+ */
+
+use std::io;
+use std::net::IpAddr;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::{NamedSocketAddr, Stream};
+
+const VERSION: u8 = 0x05;
+const METHOD_NO_AUTH: u8 = 0x00;
+const METHOD_USERNAME_PASSWORD: u8 = 0x02;
+const METHOD_NONE_ACCEPTABLE: u8 = 0xff;
+// NOTE: The username and password subnegotiation has its own version, see RFC 1929.
+const USERNAME_PASSWORD_VERSION: u8 = 0x01;
+const COMMAND_CONNECT: u8 = 0x01;
+const ADDRESS_IPV4: u8 = 0x01;
+const ADDRESS_DOMAIN: u8 = 0x03;
+const ADDRESS_IPV6: u8 = 0x04;
+
+impl Stream {
+    // Connects to a SOCKS5 proxy, which can be reached over any transport, and asks it to
+    // connect to the target. The returned stream is tunneled to the target. Hosts that
+    // aren't IP addresses are resolved by the proxy.
+    pub async fn connect_via_socks5(proxy: &NamedSocketAddr, target_host: &str, target_port: u16) -> io::Result<Stream> {
+        connect(proxy, target_host, target_port, None).await
+    }
+
+    // NOTE: The credentials are sent to the proxy in plain text.
+    pub async fn connect_via_socks5_with_auth(
+        proxy: &NamedSocketAddr,
+        target_host: &str,
+        target_port: u16,
+        username: &str,
+        password: &str,
+    ) -> io::Result<Stream> {
+        connect(proxy, target_host, target_port, Some((username, password))).await
+    }
+}
+
+async fn connect(
+    proxy: &NamedSocketAddr,
+    target_host: &str,
+    target_port: u16,
+    credentials: Option<(&str, &str)>,
+) -> io::Result<Stream> {
+    // NOTE: Validate everything before connecting, so invalid input doesn't reach the proxy.
+    let request = connect_request(target_host, target_port)?;
+    let authentication = credentials.map(|(username, password)| authentication_request(username, password)).transpose()?;
+
+    let mut stream = Stream::connect(proxy).await?;
+
+    let greeting: &[u8] = match authentication {
+        Some(_) => &[VERSION, 2, METHOD_NO_AUTH, METHOD_USERNAME_PASSWORD],
+        None => &[VERSION, 1, METHOD_NO_AUTH],
+    };
+
+    stream.write_all(greeting).await?;
+
+    let mut reply = [0; 2];
+    stream.read_exact(&mut reply).await?;
+
+    if reply[0] != VERSION {
+        return Err(invalid_reply("unknown version"))
+    }
+
+    match (reply[1], authentication) {
+        (METHOD_NO_AUTH, _) => {}
+        (METHOD_USERNAME_PASSWORD, Some(authentication)) => {
+            stream.write_all(&authentication).await?;
+            stream.read_exact(&mut reply).await?;
+
+            // NOTE: See section 2 of RFC 1929.
+            if reply[0] != USERNAME_PASSWORD_VERSION {
+                return Err(invalid_reply("unknown authentication version"))
+            }
+
+            if reply[1] != 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "Cannot authenticate to the socks proxy, the credentials were rejected.",
+                ))
+            }
+        }
+        (METHOD_NONE_ACCEPTABLE, _) => return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "Cannot authenticate to the socks proxy, none of the offered methods were accepted.",
+        )),
+        _ => return Err(invalid_reply("method was not offered")),
+    }
+
+    stream.write_all(&request).await?;
+
+    let mut reply = [0; 4];
+    stream.read_exact(&mut reply).await?;
+
+    if reply[0] != VERSION {
+        return Err(invalid_reply("unknown version"))
+    }
+
+    if reply[1] != 0 {
+        return Err(connect_error(reply[1]))
+    }
+
+    // NOTE: The address the proxy connected from isn't needed, but it has to be consumed so
+    //       the stream starts at the data sent by the target.
+    let length = match reply[3] {
+        ADDRESS_IPV4 => 4,
+        ADDRESS_IPV6 => 16,
+        ADDRESS_DOMAIN => stream.read_u8().await? as usize,
+        _ => return Err(invalid_reply("unknown address type")),
+    };
+
+    let mut bound = vec![0; length + 2];
+    stream.read_exact(&mut bound).await?;
+
+    Ok(stream)
+}
+
+fn connect_request(target_host: &str, target_port: u16) -> io::Result<Vec<u8>> {
+    let mut request = vec![VERSION, COMMAND_CONNECT, 0];
+
+    let ip_host = target_host.strip_prefix('[').and_then(|host| host.strip_suffix(']')).unwrap_or(target_host);
+
+    match ip_host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => {
+            request.push(ADDRESS_IPV4);
+            request.extend_from_slice(&ip.octets());
+        }
+        Ok(IpAddr::V6(ip)) => {
+            request.push(ADDRESS_IPV6);
+            request.extend_from_slice(&ip.octets());
+        }
+        Err(_) => {
+            if target_host.is_empty() || target_host.len() > u8::MAX as usize {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Cannot connect through a socks proxy to a host that is empty or longer than 255 bytes.",
+                ))
+            }
+
+            request.push(ADDRESS_DOMAIN);
+            request.push(target_host.len() as u8);
+            request.extend_from_slice(target_host.as_bytes());
+        }
+    }
+
+    request.extend_from_slice(&target_port.to_be_bytes());
+
+    Ok(request)
+}
+
+fn authentication_request(username: &str, password: &str) -> io::Result<Vec<u8>> {
+    if username.is_empty() || username.len() > u8::MAX as usize || password.len() > u8::MAX as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Cannot authenticate to a socks proxy with an empty username or credentials longer than 255 bytes.",
+        ))
+    }
+
+    let mut request = vec![USERNAME_PASSWORD_VERSION, username.len() as u8];
+    request.extend_from_slice(username.as_bytes());
+    request.push(password.len() as u8);
+    request.extend_from_slice(password.as_bytes());
+
+    Ok(request)
+}
+
+// See section 6 of RFC 1928.
+fn connect_error(reply: u8) -> io::Error {
+    let (kind, reason) = match reply {
+        0x02 => (io::ErrorKind::PermissionDenied, "connection not allowed by ruleset"),
+        0x03 => (io::ErrorKind::NetworkUnreachable, "network unreachable"),
+        0x04 => (io::ErrorKind::HostUnreachable, "host unreachable"),
+        0x05 => (io::ErrorKind::ConnectionRefused, "connection refused"),
+        0x06 => (io::ErrorKind::TimedOut, "TTL expired"),
+        0x07 => (io::ErrorKind::Unsupported, "command not supported"),
+        0x08 => (io::ErrorKind::Unsupported, "address type not supported"),
+        _ => (io::ErrorKind::Other, "general failure"),
+    };
+
+    io::Error::new(kind, format!("Cannot connect through the socks proxy: {}.", reason))
+}
+
+fn invalid_reply(reason: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Cannot parse socks proxy reply: {}.", reason),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_util, Listener};
+
+    // Plays the proxy side of one connection, choosing the given method and answering the
+    // connect request with reply. Returns the authentication and connect requests it read.
+    async fn proxy(listener: &Listener, method: u8, authenticated: bool, reply: u8) -> (Vec<u8>, Vec<u8>) {
+        let (mut stream, _) = listener.accept().await.unwrap();
+
+        let mut greeting = [0; 2];
+        stream.read_exact(&mut greeting).await.unwrap();
+        stream.read_exact(&mut vec![0; greeting[1] as usize]).await.unwrap();
+        stream.write_all(&[VERSION, method]).await.unwrap();
+
+        let mut authentication = Vec::new();
+
+        if method == METHOD_USERNAME_PASSWORD {
+            let mut buf = [0; 64];
+            let read = stream.read(&mut buf).await.unwrap();
+
+            authentication.extend_from_slice(&buf[..read]);
+            stream.write_all(&[USERNAME_PASSWORD_VERSION, if authenticated { 0 } else { 1 }]).await.unwrap();
+        }
+
+        let mut request = vec![0; 64];
+        let read = stream.read(&mut request).await.unwrap();
+        request.truncate(read);
+
+        if read > 0 {
+            // NOTE: Replies with an IPv6 bound address, which has to be skipped by the client.
+            stream.write_all(&[VERSION, reply, 0, ADDRESS_IPV6]).await.unwrap();
+            stream.write_all(&[0; 18]).await.unwrap();
+            stream.write_all(b"hello").await.unwrap();
+        }
+
+        (authentication, request)
+    }
+
+    #[tokio::test]
+    async fn connects_through_the_proxy() {
+        let (listener, local_addr) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+        let named_socket_addr = local_addr.to_named_socket_addr().unwrap();
+
+        let (stream, (_, request)) = tokio::join!(
+            Stream::connect_via_socks5(&named_socket_addr, "example.com", 443),
+            proxy(&listener, METHOD_NO_AUTH, false, 0),
+        );
+
+        assert_eq!(request, [&[VERSION, COMMAND_CONNECT, 0, ADDRESS_DOMAIN, 11][..], b"example.com", &443u16.to_be_bytes()].concat());
+
+        let mut buf = [0; 5];
+
+        stream.unwrap().read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"hello");
+
+        let (stream, (authentication, request)) = tokio::join!(
+            Stream::connect_via_socks5_with_auth(&named_socket_addr, "[::1]", 80, "user", "pass"),
+            proxy(&listener, METHOD_USERNAME_PASSWORD, true, 0),
+        );
+
+        stream.unwrap();
+        assert_eq!(authentication, [&[USERNAME_PASSWORD_VERSION, 4][..], b"user", &[4], b"pass"].concat());
+        assert_eq!(request[3], ADDRESS_IPV6);
+        assert_eq!(request.len(), 4 + 16 + 2);
+    }
+
+    #[tokio::test]
+    async fn proxy_errors() {
+        let (listener, local_addr) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+        let named_socket_addr = local_addr.to_named_socket_addr().unwrap();
+
+        let (stream, _) = tokio::join!(
+            Stream::connect_via_socks5_with_auth(&named_socket_addr, "127.0.0.1", 80, "user", "wrong"),
+            proxy(&listener, METHOD_USERNAME_PASSWORD, false, 0),
+        );
+
+        assert_eq!(stream.unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+
+        let (stream, _) = tokio::join!(
+            Stream::connect_via_socks5(&named_socket_addr, "127.0.0.1", 80),
+            proxy(&listener, METHOD_NONE_ACCEPTABLE, false, 0),
+        );
+
+        assert_eq!(stream.unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+
+        let (stream, _) = tokio::join!(
+            Stream::connect_via_socks5(&named_socket_addr, "127.0.0.1", 80),
+            proxy(&listener, METHOD_NO_AUTH, false, 0x05),
+        );
+
+        assert_eq!(stream.unwrap_err().kind(), io::ErrorKind::ConnectionRefused);
+
+        // NOTE: Invalid input is rejected before connecting to the proxy.
+        let unused_addr = test_util::unused_tcp_addr().await;
+
+        assert_eq!(Stream::connect_via_socks5(&unused_addr, "", 80).await.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(
+            Stream::connect_via_socks5_with_auth(&unused_addr, "example.com", 80, "", "").await.unwrap_err().kind(),
+            io::ErrorKind::InvalidInput,
+        );
+    }
+}
@@ -0,0 +1,130 @@
+/*
+ * Copyright (c) 2023, networkException <git@nwex.de>
+ *
+ * SPDX-License-Identifier: BSD-2-Clause OR MIT
+ */
+
+use std::io;
+use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::ptr;
+
+use tokio::io::Interest;
+
+use crate::Stream;
+
+// NOTE: Matches the default capacity of a pipe on Linux, the pipe is drained
+//       completely before splicing into it again so it never fills up.
+const PIPE_CHUNK_SIZE: usize = 64 * 1024;
+
+pub(crate) fn is_supported(stream: &Stream) -> bool {
+    matches!(stream, Stream::Tcp(_) | Stream::Unix(_))
+}
+
+pub(crate) async fn copy_bidirectional(a: &Stream, b: &Stream) -> io::Result<(u64, u64)> {
+    tokio::try_join!(copy(a, b), copy(b, a))
+}
+
+async fn copy(from: &Stream, to: &Stream) -> io::Result<u64> {
+    let (pipe_read, pipe_write) = pipe()?;
+    let mut copied = 0;
+
+    loop {
+        let received = async_io(from, Interest::READABLE, || splice(from.as_raw_fd(), pipe_write.as_raw_fd(), PIPE_CHUNK_SIZE)).await?;
+
+        if received == 0 {
+            break
+        }
+
+        let mut pending = received;
+
+        while pending > 0 {
+            pending -= async_io(to, Interest::WRITABLE, || splice(pipe_read.as_raw_fd(), to.as_raw_fd(), pending)).await?;
+        }
+
+        copied += received as u64;
+    }
+
+    if unsafe { libc::shutdown(to.as_raw_fd(), libc::SHUT_WR) } < 0 {
+        return Err(io::Error::last_os_error())
+    }
+
+    Ok(copied)
+}
+
+async fn async_io<R>(stream: &Stream, interest: Interest, f: impl FnMut() -> io::Result<R>) -> io::Result<R> {
+    match stream {
+        Stream::Tcp(tcp_stream) => tcp_stream.async_io(interest, f).await,
+        Stream::Unix(unix_stream) => unix_stream.async_io(interest, f).await,
+        #[cfg(feature = "vsock")]
+        _ => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "Cannot splice a vsock stream.",
+        )),
+    }
+}
+
+fn pipe() -> io::Result<(OwnedFd, OwnedFd)> {
+    let mut fds = [0; 2];
+
+    if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC | libc::O_NONBLOCK) } < 0 {
+        return Err(io::Error::last_os_error())
+    }
+
+    Ok(unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) })
+}
+
+fn splice(from: RawFd, to: RawFd, length: usize) -> io::Result<usize> {
+    let spliced = unsafe {
+        libc::splice(from, ptr::null_mut(), to, ptr::null_mut(), length, libc::SPLICE_F_MOVE | libc::SPLICE_F_NONBLOCK)
+    };
+
+    if spliced < 0 {
+        return Err(io::Error::last_os_error())
+    }
+
+    Ok(spliced as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+    use crate::test_util;
+
+    #[tokio::test]
+    async fn splices_between_tcp_and_unix() {
+        let (mut client, mut a) = test_util::tcp_pair().await;
+        let (mut b, mut upstream) = test_util::unix_pair();
+
+        assert!(is_supported(&a) && is_supported(&b));
+
+        let proxy = tokio::spawn(async move { Stream::copy_bidirectional_splice(&mut a, &mut b).await });
+
+        // NOTE: Larger than the pipe, so it is spliced in several chunks.
+        let payload = (0..3 * PIPE_CHUNK_SIZE + 7).map(|i| i as u8).collect::<Vec<_>>();
+        let mut received = Vec::new();
+
+        let (sent, read) = tokio::join!(
+            async {
+                client.write_all(&payload).await?;
+                client.shutdown().await
+            },
+            upstream.read_to_end(&mut received),
+        );
+
+        sent.unwrap();
+        read.unwrap();
+        assert!(received == payload);
+
+        upstream.write_all(b"reply").await.unwrap();
+        upstream.shutdown().await.unwrap();
+
+        let mut reply = Vec::new();
+
+        client.read_to_end(&mut reply).await.unwrap();
+        assert_eq!(reply, b"reply");
+
+        assert_eq!(proxy.await.unwrap().unwrap(), (payload.len() as u64, 5));
+    }
+}
@@ -4,24 +4,106 @@
  * SPDX-License-Identifier: BSD-2-Clause OR MIT
  */
 
+#[cfg(unix)]
+use std::os::unix::{self, io::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, RawFd}};
+#[cfg(target_os = "linux")]
+use {std::os::unix::io::OwnedFd, tokio::io::unix::AsyncFd};
+
 #[cfg(unix)]
 use tokio::net::UnixStream;
 
-use std::io;
+use std::future;
+use std::io::{self, IoSlice};
+use std::mem::MaybeUninit;
+use std::net;
 use std::pin::Pin;
-use std::task::{Context, Poll};
+use std::task::{ready, Context, Poll};
+use std::time::Duration;
+
+#[cfg(any(unix, windows))]
+use socket2::{SockRef, Socket};
+#[cfg(target_os = "linux")]
+use socket2::{Domain, SockAddr, Type};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, Interest, ReadBuf};
+use tokio::net::{TcpSocket, TcpStream};
+use tokio::task::JoinSet;
+use tokio::time;
 
-use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
-use tokio::net::TcpStream;
+#[cfg(feature = "tracing")]
+use tracing::Instrument;
 
+#[cfg(windows)]
+use tokio::net::windows::named_pipe::{NamedPipeClient, NamedPipeServer};
+
+#[cfg(all(feature = "vsock", target_os = "linux"))]
+use tokio_vsock::VsockStream;
+
+#[cfg(unix)]
+use crate::{ancillary, fd};
+use crate::{BufferedStream, Connect, CountingStream, IdentifiedStream, IdleTimeout, ResetAsEof};
 use crate::NamedSocketAddr;
+#[cfg(any(unix, windows))]
+use crate::SocketType;
 use crate::SocketAddr;
+use crate::Transport;
+#[cfg(target_os = "linux")]
+use crate::{splice, UnixSocketAddr};
+
+const MAX_CONNECT_BACKOFF: Duration = Duration::from_secs(5);
+// NOTE: The connection attempt delay recommended by section 5 of RFC 8305.
+const CONNECT_ANY_DELAY: Duration = Duration::from_millis(250);
+const SEND_QUEUE_POLL_INTERVAL: Duration = Duration::from_millis(10);
+// NOTE: Not exported by libc, see the TCP states in netinet/tcp.h.
+#[cfg(target_os = "linux")]
+const TCP_ESTABLISHED: u8 = 1;
 
 #[derive(Debug)]
 pub enum Stream {
     Tcp(TcpStream),
     #[cfg(unix)]
     Unix(UnixStream),
+    #[cfg(windows)]
+    PipeServer(NamedPipeServer),
+    #[cfg(windows)]
+    PipeClient(NamedPipeClient),
+    #[cfg(all(feature = "vsock", target_os = "linux"))]
+    Vsock(VsockStream),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueuedBytes {
+    pub unsent: usize,
+    pub unread: usize,
+}
+
+// NOTE: state is one of the TCP_* states from netinet/tcp.h, e.g. 1 for TCP_ESTABLISHED.
+//       retransmits counts the retransmissions of the current unacknowledged segment,
+//       total_retransmits those over the whole connection.
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TcpInfo {
+    pub state: u8,
+    pub rtt: Duration,
+    pub rttvar: Duration,
+    pub retransmits: u8,
+    pub total_retransmits: u32,
+}
+
+// NOTE: Credentials sent with SCM_CREDENTIALS are checked by the kernel, only privileged
+//       processes can send credentials other than their own.
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Credentials {
+    pub pid: i32,
+    pub uid: u32,
+    pub gid: u32,
+}
+
+#[derive(Debug)]
+pub enum StdStream {
+    Tcp(net::TcpStream),
+    #[cfg(unix)]
+    Unix(unix::net::UnixStream),
 }
 
 impl From<TcpStream> for Stream {
@@ -37,28 +119,1048 @@ impl From<UnixStream> for Stream {
     }
 }
 
+#[cfg(all(feature = "vsock", target_os = "linux"))]
+impl From<VsockStream> for Stream {
+    fn from(vsock_stream: VsockStream) -> Self {
+        Stream::Vsock(vsock_stream)
+    }
+}
+
+#[cfg(windows)]
+impl From<NamedPipeServer> for Stream {
+    fn from(pipe_server: NamedPipeServer) -> Self {
+        Stream::PipeServer(pipe_server)
+    }
+}
+
+#[cfg(windows)]
+impl From<NamedPipeClient> for Stream {
+    fn from(pipe_client: NamedPipeClient) -> Self {
+        Stream::PipeClient(pipe_client)
+    }
+}
+
+#[cfg(windows)]
+fn pipe_address_unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Cannot get the address of a named pipe stream.",
+    )
+}
+
 impl Stream {
+    pub fn is_tcp(&self) -> bool {
+        match self {
+            Stream::Tcp(_) => true,
+            #[cfg(unix)]
+            Stream::Unix(_) => false,
+            #[cfg(windows)]
+            Stream::PipeServer(_) | Stream::PipeClient(_) => false,
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => false,
+        }
+    }
+
+    pub fn is_unix(&self) -> bool {
+        match self {
+            Stream::Tcp(_) => false,
+            #[cfg(unix)]
+            Stream::Unix(_) => true,
+            #[cfg(windows)]
+            Stream::PipeServer(_) | Stream::PipeClient(_) => false,
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => false,
+        }
+    }
+
+    pub fn transport(&self) -> Transport {
+        match self {
+            Stream::Tcp(_) => Transport::Tcp,
+            #[cfg(unix)]
+            Stream::Unix(_) => Transport::Unix,
+            #[cfg(windows)]
+            Stream::PipeServer(_) | Stream::PipeClient(_) => Transport::Pipe,
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => Transport::Vsock,
+        }
+    }
+
+    pub fn as_tcp(&self) -> Option<&TcpStream> {
+        match self {
+            Stream::Tcp(tcp_stream) => Some(tcp_stream),
+            #[cfg(unix)]
+            Stream::Unix(_) => None,
+            #[cfg(windows)]
+            Stream::PipeServer(_) | Stream::PipeClient(_) => None,
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => None,
+        }
+    }
+
+    pub fn as_tcp_mut(&mut self) -> Option<&mut TcpStream> {
+        match self {
+            Stream::Tcp(tcp_stream) => Some(tcp_stream),
+            #[cfg(unix)]
+            Stream::Unix(_) => None,
+            #[cfg(windows)]
+            Stream::PipeServer(_) | Stream::PipeClient(_) => None,
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => None,
+        }
+    }
+
+    #[cfg(unix)]
+    pub fn as_unix(&self) -> Option<&UnixStream> {
+        match self {
+            Stream::Unix(unix_stream) => Some(unix_stream),
+            _ => None,
+        }
+    }
+
+    #[cfg(unix)]
+    pub fn as_unix_mut(&mut self) -> Option<&mut UnixStream> {
+        match self {
+            Stream::Unix(unix_stream) => Some(unix_stream),
+            _ => None,
+        }
+    }
+
     pub async fn connect(named_socket_addr: &NamedSocketAddr) -> io::Result<Self> {
+        let connect = Connect::new(named_socket_addr.clone());
+
+        // NOTE: The outcome is logged inside the span, so the events carry its fields.
+        let connect = async {
+            let result = connect.await;
+
+            #[cfg(feature = "tracing")]
+            match &result {
+                Ok(stream) => tracing::debug!(addr = %named_socket_addr, transport = %stream.transport(), "Connected"),
+                Err(error) => tracing::debug!(addr = %named_socket_addr, %error, "Cannot connect"),
+            }
+
+            result
+        };
+
+        #[cfg(feature = "tracing")]
+        let connect = connect.instrument(tracing::info_span!("connect", addr = %named_socket_addr));
+
+        connect.await
+    }
+
+    pub fn connect_future(named_socket_addr: &NamedSocketAddr) -> Connect {
+        Connect::new(named_socket_addr.clone())
+    }
+
+    pub async fn connect_from(local_inet_socket_addr: net::SocketAddr, named_socket_addr: &NamedSocketAddr) -> io::Result<Self> {
         match named_socket_addr {
-            NamedSocketAddr::Inet(inet_socket_addr) => TcpStream::connect(inet_socket_addr).await.map(Stream::Tcp),
+            NamedSocketAddr::Inet(inet_socket_addr) => {
+                let socket = match local_inet_socket_addr {
+                    net::SocketAddr::V4(_) => TcpSocket::new_v4()?,
+                    net::SocketAddr::V6(_) => TcpSocket::new_v6()?,
+                };
+
+                socket.bind(local_inet_socket_addr)?;
+                socket.connect(*inet_socket_addr).await.map(Stream::Tcp)
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot connect from a local inet address to a non inet address.",
+            )),
+        }
+    }
+
+    // Staggers connection attempts like RFC 8305 (Happy Eyeballs), starting to connect to the
+    // next address once the previous attempt failed or CONNECT_ANY_DELAY elapsed. The first
+    // established connection is returned and the attempts still in progress are cancelled.
+    pub async fn connect_any(named_socket_addrs: &[NamedSocketAddr]) -> io::Result<Self> {
+        let mut named_socket_addrs = named_socket_addrs.iter().peekable();
+        let mut attempts = JoinSet::new();
+        let mut errors = Vec::new();
+
+        loop {
+            if let Some(named_socket_addr) = named_socket_addrs.next().cloned() {
+                attempts.spawn(async move {
+                    let result = Stream::connect(&named_socket_addr).await;
+
+                    (named_socket_addr, result)
+                });
+            }
+
+            let joined = if named_socket_addrs.peek().is_some() {
+                match time::timeout(CONNECT_ANY_DELAY, attempts.join_next()).await {
+                    Ok(joined) => joined,
+                    Err(_elapsed) => continue,
+                }
+            } else {
+                attempts.join_next().await
+            };
+
+            match joined {
+                Some(Ok((_, Ok(stream)))) => return Ok(stream),
+                Some(Ok((named_socket_addr, Err(error)))) => errors.push((named_socket_addr, error)),
+                Some(Err(join_error)) => return Err(join_error.into()),
+                None => break,
+            }
+        }
+
+        if errors.len() <= 1 {
+            return Err(errors.pop().map(|(_, error)| error).unwrap_or_else(|| io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Cannot connect without any address.",
+            )))
+        }
+
+        let message = errors
+            .iter()
+            .map(|(named_socket_addr, error)| format!("{}: {}", named_socket_addr, error))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        Err(io::Error::new(
+            errors.last().unwrap().1.kind(),
+            format!("Cannot connect to any address ({}).", message),
+        ))
+    }
+
+    // Retries connecting while the address is refused or missing (e.g. the server
+    // has not created its socket yet), doubling the backoff up to MAX_CONNECT_BACKOFF.
+    pub async fn connect_retry(named_socket_addr: &NamedSocketAddr, retries: usize, initial_backoff: Duration) -> io::Result<Self> {
+        let mut backoff = initial_backoff.min(MAX_CONNECT_BACKOFF);
+        let mut attempt = 0;
+
+        loop {
+            match Stream::connect(named_socket_addr).await {
+                Ok(stream) => return Ok(stream),
+                Err(error) if attempt < retries && matches!(error.kind(), io::ErrorKind::ConnectionRefused | io::ErrorKind::NotFound) => {
+                    time::sleep(backoff).await;
+
+                    backoff = backoff.saturating_mul(2).min(MAX_CONNECT_BACKOFF);
+                    attempt += 1;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    pub async fn readable(&self) -> io::Result<()> {
+        match self {
+            Stream::Tcp(tcp_stream) => tcp_stream.readable().await,
+            #[cfg(unix)]
+            Stream::Unix(unix_stream) => unix_stream.readable().await,
+            #[cfg(windows)]
+            Stream::PipeServer(pipe_server) => pipe_server.readable().await,
+            #[cfg(windows)]
+            Stream::PipeClient(pipe_client) => pipe_client.readable().await,
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot wait for readiness of a vsock stream.",
+            )),
+        }
+    }
+
+    pub async fn writable(&self) -> io::Result<()> {
+        match self {
+            Stream::Tcp(tcp_stream) => tcp_stream.writable().await,
+            #[cfg(unix)]
+            Stream::Unix(unix_stream) => unix_stream.writable().await,
+            #[cfg(windows)]
+            Stream::PipeServer(pipe_server) => pipe_server.writable().await,
+            #[cfg(windows)]
+            Stream::PipeClient(pipe_client) => pipe_client.writable().await,
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot wait for readiness of a vsock stream.",
+            )),
+        }
+    }
+
+    // Returns the bytes waiting in the kernel send and receive queues, for diagnosing
+    // backpressure. Only supported for TCP and Unix streams on Linux.
+    // NOTE: For Unix streams the send queue is reported as the memory it uses, which
+    //       includes the kernel's bookkeeping overhead.
+    pub fn queued_bytes(&self) -> io::Result<QueuedBytes> {
+        #[cfg(target_os = "linux")]
+        if matches!(self, Stream::Tcp(_) | Stream::Unix(_)) {
+            let (unsent, unread) = fd::queued_bytes(self.as_raw_fd())?;
+
+            return Ok(QueuedBytes { unsent, unread })
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "Cannot get the queued bytes of this stream on this system.",
+        ))
+    }
+
+    // NOTE: Like the methods of the tokio types, only the last task polling for read or
+    //       write readiness is woken up. Once ready, try_read and try_write can be called
+    //       until they fail with io::ErrorKind::WouldBlock.
+    pub fn poll_read_ready(&self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self {
+            Stream::Tcp(tcp_stream) => tcp_stream.poll_read_ready(cx),
+            #[cfg(unix)]
+            Stream::Unix(unix_stream) => unix_stream.poll_read_ready(cx),
+            #[cfg(windows)]
+            Stream::PipeServer(pipe_server) => pipe_server.poll_read_ready(cx),
+            #[cfg(windows)]
+            Stream::PipeClient(pipe_client) => pipe_client.poll_read_ready(cx),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot wait for readiness of a vsock stream.",
+            ))),
+        }
+    }
+
+    pub fn poll_write_ready(&self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self {
+            Stream::Tcp(tcp_stream) => tcp_stream.poll_write_ready(cx),
+            #[cfg(unix)]
+            Stream::Unix(unix_stream) => unix_stream.poll_write_ready(cx),
+            #[cfg(windows)]
+            Stream::PipeServer(pipe_server) => pipe_server.poll_write_ready(cx),
+            #[cfg(windows)]
+            Stream::PipeClient(pipe_client) => pipe_client.poll_write_ready(cx),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot wait for readiness of a vsock stream.",
+            ))),
+        }
+    }
+
+    pub fn try_read(&self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(tcp_stream) => tcp_stream.try_read(buf),
+            #[cfg(unix)]
+            Stream::Unix(unix_stream) => unix_stream.try_read(buf),
+            #[cfg(windows)]
+            Stream::PipeServer(pipe_server) => pipe_server.try_read(buf),
+            #[cfg(windows)]
+            Stream::PipeClient(pipe_client) => pipe_client.try_read(buf),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot try to read from a vsock stream without blocking.",
+            )),
+        }
+    }
+
+    pub fn try_write(&self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(tcp_stream) => tcp_stream.try_write(buf),
+            #[cfg(unix)]
+            Stream::Unix(unix_stream) => unix_stream.try_write(buf),
+            #[cfg(windows)]
+            Stream::PipeServer(pipe_server) => pipe_server.try_write(buf),
+            #[cfg(windows)]
+            Stream::PipeClient(pipe_client) => pipe_client.try_write(buf),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot try to write to a vsock stream without blocking.",
+            )),
+        }
+    }
+
+    // Returns false if the stream didn't become readable before the timeout elapsed.
+    pub async fn readable_timeout(&self, timeout: Duration) -> io::Result<bool> {
+        match time::timeout(timeout, self.readable()).await {
+            Ok(result) => result.map(|_| true),
+            Err(_elapsed) => Ok(false),
+        }
+    }
+
+    // Returns false if the stream didn't become writable before the timeout elapsed.
+    pub async fn writable_timeout(&self, timeout: Duration) -> io::Result<bool> {
+        match time::timeout(timeout, self.writable()).await {
+            Ok(result) => result.map(|_| true),
+            Err(_elapsed) => Ok(false),
+        }
+    }
+
+    // Fails with io::ErrorKind::TimedOut if nothing was read before the timeout elapsed.
+    pub async fn read_timeout(&mut self, buf: &mut [u8], timeout: Duration) -> io::Result<usize> {
+        match time::timeout(timeout, self.read(buf)).await {
+            Ok(result) => result,
+            Err(_elapsed) => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "Cannot read from the stream before the timeout elapsed.",
+            )),
+        }
+    }
+
+    // Fails with io::ErrorKind::TimedOut if nothing was written before the timeout elapsed.
+    pub async fn write_timeout(&mut self, buf: &[u8], timeout: Duration) -> io::Result<usize> {
+        match time::timeout(timeout, self.write(buf)).await {
+            Ok(result) => result,
+            Err(_elapsed) => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "Cannot write to the stream before the timeout elapsed.",
+            )),
+        }
+    }
+
+    // NOTE: The slices are advanced while writing, their contents are unspecified afterwards.
+    pub async fn write_all_vectored(&mut self, mut bufs: &mut [IoSlice<'_>]) -> io::Result<()> {
+        IoSlice::advance_slices(&mut bufs, 0);
+
+        while !bufs.is_empty() {
+            match future::poll_fn(|cx| Pin::new(&mut *self).poll_write_vectored(cx, bufs)).await {
+                Ok(0) => return Err(io::Error::new(
+                    io::ErrorKind::WriteZero,
+                    "Cannot write the whole buffer.",
+                )),
+                Ok(written) => IoSlice::advance_slices(&mut bufs, written),
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(error),
+            }
+        }
+
+        Ok(())
+    }
+
+    // Reads one message prefixed by its length as a big endian u32. Lengths above max_len are
+    // rejected with io::ErrorKind::InvalidData before allocating, the stream can't be used
+    // for framing afterwards. The end of the stream within a message fails with
+    // io::ErrorKind::UnexpectedEof.
+    pub async fn read_framed_u32(&mut self, max_len: usize) -> io::Result<Vec<u8>> {
+        let length = self.read_u32().await? as usize;
+
+        if length > max_len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Cannot read a frame of {} bytes, which exceeds the maximum of {} bytes.", length, max_len),
+            ))
+        }
+
+        let mut data = vec![0; length];
+        self.read_exact(&mut data).await?;
+
+        Ok(data)
+    }
+
+    pub async fn write_framed_u32(&mut self, data: &[u8]) -> io::Result<()> {
+        let length = u32::try_from(data.len()).map_err(|_| io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Cannot write a frame longer than u32::MAX bytes.",
+        ))?;
+
+        let prefix = length.to_be_bytes();
+
+        self.write_all_vectored(&mut [IoSlice::new(&prefix), IoSlice::new(data)]).await
+    }
+
+    // Flushes and shuts down the write side, then waits for the send queue to drain so data
+    // isn't lost when the stream is dropped. Fails with io::ErrorKind::TimedOut if data is
+    // still queued once the timeout elapsed.
+    // NOTE: For TCP the queue drains once the peer acknowledged the data, for Unix streams
+    //       once the peer read it. Where queued_bytes is unsupported, this only flushes and
+    //       shuts down.
+    pub async fn close_gracefully(&mut self, timeout: Duration) -> io::Result<()> {
+        let close = async {
+            self.flush().await?;
+            self.shutdown().await?;
+
+            loop {
+                match self.queued_bytes() {
+                    Ok(queued_bytes) if queued_bytes.unsent == 0 => return Ok(()),
+                    Ok(_) => time::sleep(SEND_QUEUE_POLL_INTERVAL).await,
+                    Err(error) if error.kind() == io::ErrorKind::Unsupported => return Ok(()),
+                    Err(error) => return Err(error),
+                }
+            }
+        };
+
+        match time::timeout(timeout, close).await {
+            Ok(result) => result,
+            Err(_elapsed) => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "Cannot close the stream gracefully before the timeout elapsed, data is still queued.",
+            )),
+        }
+    }
+
+    // Lingering close: Shuts down the write side and then reads and discards up to max_bytes
+    // until the peer closes its side or the timeout elapses. Closing a socket with unread data
+    // makes the kernel send a reset, which can make the peer lose the last response sent.
+    // NOTE: The write side is shut down first so a peer waiting for the end of the stream
+    //       before closing its side doesn't stall the drain until the timeout.
+    pub async fn drain_and_shutdown(&mut self, max_bytes: usize, timeout: Duration) -> io::Result<()> {
+        self.shutdown().await?;
+
+        let drain = async {
+            let mut buf = [0; 4096];
+            let mut drained = 0;
+
+            while drained < max_bytes {
+                let length = buf.len().min(max_bytes - drained);
+
+                match self.read(&mut buf[..length]).await? {
+                    0 => break,
+                    read => drained += read,
+                }
+            }
+
+            Ok(())
+        };
+
+        match time::timeout(timeout, drain).await {
+            Ok(result) => result,
+            Err(_elapsed) => Ok(()),
+        }
+    }
+
+    // Copies data in both directions until both sides reached the end of their stream,
+    // returning the bytes copied from a to b and from b to a. On Linux, TCP and Unix
+    // streams are spliced through a pipe without copying the data into userspace.
+    pub async fn copy_bidirectional_splice(a: &mut Stream, b: &mut Stream) -> io::Result<(u64, u64)> {
+        #[cfg(target_os = "linux")]
+        if splice::is_supported(a) && splice::is_supported(b) {
+            return splice::copy_bidirectional(a, b).await
+        }
+
+        tokio::io::copy_bidirectional(a, b).await
+    }
+
+    pub fn buffered(self) -> BufferedStream {
+        BufferedStream::new(self)
+    }
+
+    pub fn buffered_with_capacity(self, read_capacity: usize, write_capacity: usize) -> BufferedStream {
+        BufferedStream::with_capacity(self, read_capacity, write_capacity)
+    }
+
+    pub fn with_counters(self) -> CountingStream {
+        CountingStream::new(self)
+    }
+
+    // Wraps the stream to give it a process unique id, e.g. for correlating logs of a
+    // connection, see IdentifiedStream.
+    pub fn with_id(self) -> IdentifiedStream {
+        IdentifiedStream::new(self)
+    }
+
+    pub fn with_idle_timeout(self, timeout: Duration) -> IdleTimeout<Stream> {
+        IdleTimeout::new(self, timeout)
+    }
+
+    // Wraps the stream so a reset connection reads like a clean close, see ResetAsEof.
+    pub fn with_reset_as_eof(self) -> ResetAsEof<Stream> {
+        ResetAsEof::new(self)
+    }
+
+    // NOTE: The returned socket is still in nonblocking mode.
+    pub fn into_std(self) -> io::Result<StdStream> {
+        match self {
+            Stream::Tcp(tcp_stream) => tcp_stream.into_std().map(StdStream::Tcp),
             #[cfg(unix)]
-            NamedSocketAddr::Unix(path) => UnixStream::connect(path).await.map(Stream::Unix)
+            Stream::Unix(unix_stream) => unix_stream.into_std().map(StdStream::Unix),
+            #[cfg(any(windows, all(feature = "vsock", target_os = "linux")))]
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot convert a stream without a std equivalent.",
+            )),
+        }
+    }
+
+    // NOTE: Unlike the tokio functions, this puts the socket into nonblocking mode.
+    pub fn from_std(std_stream: StdStream) -> io::Result<Self> {
+        match std_stream {
+            StdStream::Tcp(tcp_stream) => {
+                tcp_stream.set_nonblocking(true)?;
+                TcpStream::from_std(tcp_stream).map(Stream::Tcp)
+            }
+            #[cfg(unix)]
+            StdStream::Unix(unix_stream) => {
+                unix_stream.set_nonblocking(true)?;
+                UnixStream::from_std(unix_stream).map(Stream::Unix)
+            }
         }
     }
 
     pub fn local_addr(&self) -> io::Result<SocketAddr> {
         match self {
-            Stream::Tcp(tcp_stream) => tcp_stream.local_addr().map(SocketAddr::Inet),
-            #[cfg(unix)]
-            Stream::Unix(unix_stream) => Ok(SocketAddr::Unix(unix_stream.local_addr()?.into())),
+            Stream::Tcp(tcp_stream) => tcp_stream.local_addr().map(SocketAddr::Inet),
+            #[cfg(unix)]
+            Stream::Unix(unix_stream) => Ok(SocketAddr::Unix(unix_stream.local_addr()?.into())),
+            #[cfg(windows)]
+            Stream::PipeServer(_) | Stream::PipeClient(_) => Err(pipe_address_unsupported()),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(vsock_stream) => vsock_stream
+                .local_addr()
+                .map(|vsock_addr| SocketAddr::Vsock { cid: vsock_addr.cid(), port: vsock_addr.port() }),
+        }
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        match self {
+            Stream::Tcp(tcp_stream) => tcp_stream.peer_addr().map(SocketAddr::Inet),
+            #[cfg(unix)]
+            Stream::Unix(unix_stream) => Ok(SocketAddr::Unix(unix_stream.peer_addr()?.into())),
+            #[cfg(windows)]
+            Stream::PipeServer(_) | Stream::PipeClient(_) => Err(pipe_address_unsupported()),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(vsock_stream) => vsock_stream
+                .peer_addr()
+                .map(|vsock_addr| SocketAddr::Vsock { cid: vsock_addr.cid(), port: vsock_addr.port() }),
+        }
+    }
+
+    // NOTE: On Unix this compares the sockets themselves, so only handles of the same socket
+    //       (e.g. from try_clone) are the same connection. Elsewhere TCP streams are compared
+    //       by their local and peer address, which can false-positive when a new connection
+    //       reuses both addresses of a closed one. Named pipes are never the same connection.
+    pub fn same_connection(&self, other: &Stream) -> bool {
+        if self.transport() != other.transport() {
+            return false
+        }
+
+        #[cfg(unix)]
+        return matches!(
+            (fd::socket_identity(self.as_raw_fd()), fd::socket_identity(other.as_raw_fd())),
+            (Ok(identity), Ok(other_identity)) if identity == other_identity
+        );
+
+        #[cfg(not(unix))]
+        return match (self, other) {
+            (Stream::Tcp(tcp_stream), Stream::Tcp(other_tcp_stream)) => matches!(
+                (tcp_stream.local_addr(), tcp_stream.peer_addr(), other_tcp_stream.local_addr(), other_tcp_stream.peer_addr()),
+                (Ok(local), Ok(peer), Ok(other_local), Ok(other_peer)) if local == other_local && peer == other_peer
+            ),
+            #[cfg(windows)]
+            _ => false,
+        };
+    }
+}
+
+// NOTE: The kernel may round or double the requested buffer sizes, Linux for
+//       example doubles them to account for bookkeeping overhead.
+#[cfg(any(unix, windows))]
+impl Stream {
+    fn sock_ref(&self) -> io::Result<SockRef<'_>> {
+        #[cfg(unix)]
+        return Ok(SockRef::from(self));
+
+        #[cfg(windows)]
+        match self {
+            Stream::Tcp(tcp_stream) => Ok(SockRef::from(tcp_stream)),
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot access socket options of a named pipe stream.",
+            )),
+        }
+    }
+
+    // Gives access to socket options this crate doesn't wrap. The socket is only
+    // borrowed, it isn't closed when the closure returns.
+    pub fn with_socket2<R, F>(&self, f: F) -> io::Result<R>
+    where
+        F: FnOnce(&Socket) -> io::Result<R>,
+    {
+        f(&*self.sock_ref()?)
+    }
+
+    // Reads SO_TYPE, see SocketType.
+    pub fn socket_type(&self) -> io::Result<SocketType> {
+        self.sock_ref()?.r#type().map(SocketType::from)
+    }
+
+    pub fn recv_buffer_size(&self) -> io::Result<usize> {
+        self.sock_ref()?.recv_buffer_size()
+    }
+
+    pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.sock_ref()?.set_recv_buffer_size(size)
+    }
+
+    pub fn send_buffer_size(&self) -> io::Result<usize> {
+        self.sock_ref()?.send_buffer_size()
+    }
+
+    pub fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.sock_ref()?.set_send_buffer_size(size)
+    }
+
+    // Returns true once the peer shut down its write half or closed the connection, meaning
+    // it won't send any more data. Never waits and doesn't consume any data, as long as unread
+    // data is pending this returns false. A reset connection is reported as an error instead.
+    // NOTE: This peeks on the socket directly, try_io would fail with io::ErrorKind::WouldBlock
+    //       without peeking while tokio hasn't seen the socket become readable yet.
+    pub fn is_read_closed(&self) -> io::Result<bool> {
+        match self.sock_ref()?.peek(&mut [MaybeUninit::uninit()]) {
+            Ok(peeked) => Ok(peeked == 0),
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+
+    // Waits until the socket is readable, then resolves with true if the peer shut down its
+    // write half or closed the connection and false if data is pending instead, which has to
+    // be read before the end of the stream can be seen. Doesn't consume any data.
+    pub fn poll_peer_closed(&self, cx: &mut Context<'_>) -> Poll<io::Result<bool>> {
+        let socket = self.sock_ref()?;
+        let peek = || socket.peek(&mut [MaybeUninit::uninit()]);
+
+        loop {
+            ready!(self.poll_read_ready(cx))?;
+
+            let result = match self {
+                Stream::Tcp(tcp_stream) => tcp_stream.try_io(Interest::READABLE, peek),
+                #[cfg(unix)]
+                Stream::Unix(unix_stream) => unix_stream.try_io(Interest::READABLE, peek),
+                #[cfg(any(windows, all(feature = "vsock", target_os = "linux")))]
+                _ => peek(),
+            };
+
+            match result {
+                Ok(peeked) => return Poll::Ready(Ok(peeked == 0)),
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => {}
+                Err(error) => return Poll::Ready(Err(error)),
+            }
+        }
+    }
+
+    pub async fn peer_closed(&self) -> io::Result<bool> {
+        future::poll_fn(|cx| self.poll_peer_closed(cx)).await
+    }
+}
+
+#[cfg(unix)]
+impl Stream {
+    pub async fn send_with_fds(&self, buf: &[u8], fds: &[RawFd]) -> io::Result<usize> {
+        match self {
+            Stream::Unix(unix_stream) => unix_stream
+                .async_io(Interest::WRITABLE, || ancillary::send_with_fds(unix_stream.as_raw_fd(), buf, fds))
+                .await,
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot pass file descriptors over a non unix stream.",
+            )),
+        }
+    }
+
+    pub async fn recv_with_fds(&self, buf: &mut [u8], fds: &mut Vec<RawFd>) -> io::Result<usize> {
+        match self {
+            Stream::Unix(unix_stream) => unix_stream
+                .async_io(Interest::READABLE, || ancillary::recv_with_fds(unix_stream.as_raw_fd(), buf, fds))
+                .await,
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot pass file descriptors over a non unix stream.",
+            )),
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Stream {
+    /// # Safety
+    ///
+    /// The file descriptor must be an open, connected TCP socket that is not
+    /// owned by anything else, ownership is transferred to the returned stream.
+    pub unsafe fn from_raw_tcp_fd(fd: RawFd) -> io::Result<Stream> {
+        Stream::from_std(StdStream::Tcp(net::TcpStream::from_raw_fd(fd)))
+    }
+
+    /// # Safety
+    ///
+    /// The file descriptor must be an open, connected Unix stream socket that is
+    /// not owned by anything else, ownership is transferred to the returned stream.
+    pub unsafe fn from_raw_unix_fd(fd: RawFd) -> io::Result<Stream> {
+        Stream::from_std(StdStream::Unix(unix::net::UnixStream::from_raw_fd(fd)))
+    }
+
+    /// # Safety
+    ///
+    /// The file descriptor must be an open, connected TCP, Unix or vsock stream socket
+    /// that is not owned by anything else, ownership is transferred to the returned stream.
+    /// The file descriptor is closed if this fails.
+    // NOTE: The kind of socket is detected using getsockname.
+    pub unsafe fn from_raw_socket_fd(fd: RawFd) -> io::Result<Stream> {
+        match fd::socket_domain(fd) {
+            Ok(domain) => Stream::from_raw_fd_with_domain(fd, domain),
+            Err(error) => {
+                libc::close(fd);
+                Err(error)
+            }
+        }
+    }
+
+    // NOTE: Sockets created by this crate are close on exec from the start. Toggling the
+    //       flag afterwards isn't atomic, a process spawned concurrently from another
+    //       thread may or may not inherit the socket.
+    pub fn set_cloexec(&self, cloexec: bool) -> io::Result<()> {
+        fd::set_cloexec(self.as_raw_fd(), cloexec)
+    }
+
+    // NOTE: The tokio reactor relies on sockets being nonblocking, a blocking socket that is
+    //       still registered with it blocks the whole runtime thread on the next operation.
+    //       Only make the socket blocking right before or after leaving the reactor, e.g.
+    //       when converting it using into_std.
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        fd::set_nonblocking(self.as_raw_fd(), nonblocking)
+    }
+
+    // Sets the type of service (IP_TOS, or IPV6_TCLASS for IPv6) of outgoing packets, which
+    // carries the DSCP value used for QoS. Only supported for TCP streams.
+    pub fn set_tos(&self, tos: u32) -> io::Result<()> {
+        let (fd, ipv6) = self.tos_socket()?;
+
+        fd::set_tos(fd, ipv6, tos)
+    }
+
+    pub fn tos(&self) -> io::Result<u32> {
+        let (fd, ipv6) = self.tos_socket()?;
+
+        fd::tos(fd, ipv6)
+    }
+
+    fn tos_socket(&self) -> io::Result<(RawFd, bool)> {
+        match self {
+            Stream::Tcp(tcp_stream) => {
+                let ipv6 = match tcp_stream.local_addr()? {
+                    net::SocketAddr::V6(inet_socket_addr) => inet_socket_addr.ip().to_ipv4_mapped().is_none(),
+                    net::SocketAddr::V4(_) => false,
+                };
+
+                Ok((tcp_stream.as_raw_fd(), ipv6))
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot set the type of service of a non tcp stream.",
+            )),
+        }
+    }
+
+    unsafe fn from_raw_fd_with_domain(fd: RawFd, domain: libc::c_int) -> io::Result<Stream> {
+        match domain {
+            libc::AF_UNIX => Stream::from_raw_unix_fd(fd),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            libc::AF_VSOCK => VsockStream::new(vsock::VsockStream::from_raw_fd(fd)).map(Stream::Vsock),
+            _ => Stream::from_raw_tcp_fd(fd),
+        }
+    }
+
+    // Adopts a duplicate of fd, which stays owned by the caller.
+    fn from_duplicate_of(fd: RawFd) -> io::Result<Stream> {
+        let domain = fd::socket_domain(fd)?;
+        let duplicate = fd::duplicate(fd)?;
+
+        unsafe { Stream::from_raw_fd_with_domain(duplicate, domain) }
+    }
+
+    // NOTE: Both handles refer to the same socket in the kernel. Data read through one
+    //       isn't seen by the other, socket options and shutdown apply to both and the
+    //       connection is only closed once both handles are dropped.
+    pub fn try_clone(&self) -> io::Result<Stream> {
+        Stream::from_duplicate_of(self.as_raw_fd())
+    }
+
+    // See NamedSocketAddr::inherited_fd.
+    pub(crate) fn from_inherited(fd: RawFd) -> io::Result<Stream> {
+        fd::check_inherited(fd, false)?;
+        Stream::from_duplicate_of(fd)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Stream {
+    // Binds a Unix socket to an abstract name picked by the kernel before connecting, so the
+    // peer sees a distinct address instead of an unnamed one. Other addresses are connected
+    // to like Stream::connect does.
+    pub async fn connect_autobind(named_socket_addr: &NamedSocketAddr) -> io::Result<Stream> {
+        let path = match named_socket_addr {
+            NamedSocketAddr::Unix(path) if named_socket_addr.inherited_fd().is_none() => path,
+            _ => return Stream::connect(named_socket_addr).await,
+        };
+
+        let socket = Socket::new(Domain::UNIX, Type::STREAM, None)?;
+
+        // NOTE: Binding to an address containing only the address family makes the kernel
+        //       pick a unique abstract name.
+        let (sockaddr, length) = UnixSocketAddr::Unnamed.to_sockaddr_bytes()?;
+
+        if unsafe { libc::bind(socket.as_raw_fd(), (&sockaddr as *const libc::sockaddr_un).cast(), length) } < 0 {
+            return Err(io::Error::last_os_error())
+        }
+
+        socket.set_nonblocking(true)?;
+
+        let socket = AsyncFd::new(socket)?;
+
+        fd::connect(&socket, &SockAddr::unix(path)?).await?;
+
+        UnixStream::from_std(unix::net::UnixStream::from(OwnedFd::from(socket.into_inner()))).map(Stream::Unix)
+    }
+
+    // Sets the SO_MARK used by policy routing and firewall rules for packets sent over
+    // this socket. Requires CAP_NET_ADMIN, fails with io::ErrorKind::PermissionDenied otherwise.
+    pub fn set_mark(&self, mark: u32) -> io::Result<()> {
+        fd::set_mark(self.as_raw_fd(), mark)
+    }
+
+    pub fn mark(&self) -> io::Result<u32> {
+        fd::mark(self.as_raw_fd())
+    }
+
+    // Returns the destination a connection redirected by netfilter was originally sent to,
+    // for transparent proxies. Fails with io::ErrorKind::Unsupported if it wasn't redirected.
+    pub fn original_dst(&self) -> io::Result<net::SocketAddr> {
+        match self {
+            Stream::Tcp(tcp_stream) => {
+                let ipv6 = match tcp_stream.local_addr()? {
+                    net::SocketAddr::V6(inet_socket_addr) => inet_socket_addr.ip().to_ipv4_mapped().is_none(),
+                    net::SocketAddr::V4(_) => false,
+                };
+
+                fd::original_dst(tcp_stream.as_raw_fd(), ipv6)
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot get the original destination of a non tcp stream.",
+            )),
+        }
+    }
+
+    // Enables SO_PASSCRED, which has to be set on the receiving side before the peer sends the
+    // data recv_with_creds should return credentials for.
+    pub fn set_pass_cred(&self, pass_cred: bool) -> io::Result<()> {
+        match self {
+            Stream::Unix(unix_stream) => fd::set_pass_cred(unix_stream.as_raw_fd(), pass_cred),
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot pass credentials over a non unix stream.",
+            )),
+        }
+    }
+
+    pub fn pass_cred(&self) -> io::Result<bool> {
+        match self {
+            Stream::Unix(unix_stream) => fd::pass_cred(unix_stream.as_raw_fd()),
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot pass credentials over a non unix stream.",
+            )),
+        }
+    }
+
+    // Sends the data with credentials attached using SCM_CREDENTIALS, which unlike the peer
+    // credentials of the socket identify the sender of this particular message.
+    pub async fn send_with_creds(&self, buf: &[u8], creds: Credentials) -> io::Result<usize> {
+        let ucred = libc::ucred { pid: creds.pid, uid: creds.uid, gid: creds.gid };
+
+        match self {
+            Stream::Unix(unix_stream) => unix_stream
+                .async_io(Interest::WRITABLE, || ancillary::send_with_creds(unix_stream.as_raw_fd(), buf, &ucred))
+                .await,
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot pass credentials over a non unix stream.",
+            )),
+        }
+    }
+
+    // NOTE: With SO_PASSCRED enabled the kernel attaches the credentials of the sender to every
+    //       message, even if it didn't send any explicitly. Returns None if it is disabled.
+    pub async fn recv_with_creds(&self, buf: &mut [u8]) -> io::Result<(usize, Option<Credentials>)> {
+        let (size, ucred) = match self {
+            Stream::Unix(unix_stream) => unix_stream
+                .async_io(Interest::READABLE, || ancillary::recv_with_creds(unix_stream.as_raw_fd(), buf))
+                .await?,
+            _ => return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot pass credentials over a non unix stream.",
+            )),
+        };
+
+        Ok((size, ucred.map(|ucred| Credentials { pid: ucred.pid, uid: ucred.uid, gid: ucred.gid })))
+    }
+
+    // Reads RTT and retransmission statistics of a TCP connection from TCP_INFO, e.g. for
+    // health checks.
+    pub fn tcp_info(&self) -> io::Result<TcpInfo> {
+        let info = match self {
+            Stream::Tcp(tcp_stream) => fd::tcp_info(tcp_stream.as_raw_fd())?,
+            _ => return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot get tcp info of a non tcp stream.",
+            )),
+        };
+
+        Ok(TcpInfo {
+            state: info.state,
+            rtt: Duration::from_micros(info.rtt as u64),
+            rttvar: Duration::from_micros(info.rttvar as u64),
+            retransmits: info.retransmits,
+            total_retransmits: info.total_retrans,
+        })
+    }
+
+    // Restricts the stream to sending and receiving packets over the given network interface.
+    pub fn bind_to_device(&self, interface: &str) -> io::Result<()> {
+        match self {
+            Stream::Tcp(tcp_stream) => fd::bind_to_device(tcp_stream.as_raw_fd(), interface),
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Cannot bind a non tcp stream to a device.",
+            )),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl TcpInfo {
+    pub fn is_established(&self) -> bool {
+        self.state == TCP_ESTABLISHED
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Credentials {
+    // The credentials of the calling process, which can be sent without privileges.
+    pub fn current() -> Credentials {
+        unsafe {
+            Credentials {
+                pid: libc::getpid(),
+                uid: libc::getuid(),
+                gid: libc::getgid(),
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+impl AsRawFd for Stream {
+    fn as_raw_fd(&self) -> RawFd {
+        match self {
+            Stream::Tcp(tcp_stream) => tcp_stream.as_raw_fd(),
+            Stream::Unix(unix_stream) => unix_stream.as_raw_fd(),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(vsock_stream) => vsock_stream.as_raw_fd(),
         }
     }
+}
 
-    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+#[cfg(unix)]
+impl AsFd for Stream {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // SAFETY: The file descriptor stays open as long as the stream is borrowed.
+        unsafe { BorrowedFd::borrow_raw(self.as_raw_fd()) }
+    }
+}
+
+// NOTE: Stream and Listener don't implement IntoRawFd and FromRawFd since leaving or joining
+//       the tokio reactor can fail. Use into_std and the std types instead, or the fallible
+//       from_raw_*_fd constructors.
+#[cfg(unix)]
+impl IntoRawFd for StdStream {
+    fn into_raw_fd(self) -> RawFd {
         match self {
-            Stream::Tcp(tcp_stream) => tcp_stream.peer_addr().map(SocketAddr::Inet),
-            #[cfg(unix)]
-            Stream::Unix(unix_stream) => Ok(SocketAddr::Unix(unix_stream.local_addr()?.into())),
+            StdStream::Tcp(tcp_stream) => tcp_stream.into_raw_fd(),
+            StdStream::Unix(unix_stream) => unix_stream.into_raw_fd(),
         }
     }
 }
@@ -73,6 +1175,12 @@ impl AsyncRead for Stream {
             Stream::Tcp(tcp_stream) => Pin::new(tcp_stream).poll_read(cx, buf),
             #[cfg(unix)]
             Stream::Unix(unix_stream) => Pin::new(unix_stream).poll_read(cx, buf),
+            #[cfg(windows)]
+            Stream::PipeServer(pipe_server) => Pin::new(pipe_server).poll_read(cx, buf),
+            #[cfg(windows)]
+            Stream::PipeClient(pipe_client) => Pin::new(pipe_client).poll_read(cx, buf),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(vsock_stream) => Pin::new(vsock_stream).poll_read(cx, buf),
         }
     }
 }
@@ -87,6 +1195,12 @@ impl AsyncWrite for Stream {
             Stream::Tcp(tcp_stream) => Pin::new(tcp_stream).poll_write(cx, buf),
             #[cfg(unix)]
             Stream::Unix(unix_stream) => Pin::new(unix_stream).poll_write(cx, buf),
+            #[cfg(windows)]
+            Stream::PipeServer(pipe_server) => Pin::new(pipe_server).poll_write(cx, buf),
+            #[cfg(windows)]
+            Stream::PipeClient(pipe_client) => Pin::new(pipe_client).poll_write(cx, buf),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(vsock_stream) => Pin::new(vsock_stream).poll_write(cx, buf),
         }
     }
 
@@ -99,6 +1213,12 @@ impl AsyncWrite for Stream {
             Stream::Tcp(tcp_stream) => Pin::new(tcp_stream).poll_write_vectored(cx, bufs),
             #[cfg(unix)]
             Stream::Unix(unix_stream) => Pin::new(unix_stream).poll_write_vectored(cx, bufs),
+            #[cfg(windows)]
+            Stream::PipeServer(pipe_server) => Pin::new(pipe_server).poll_write_vectored(cx, bufs),
+            #[cfg(windows)]
+            Stream::PipeClient(pipe_client) => Pin::new(pipe_client).poll_write_vectored(cx, bufs),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(vsock_stream) => Pin::new(vsock_stream).poll_write_vectored(cx, bufs),
         }
     }
 
@@ -107,6 +1227,12 @@ impl AsyncWrite for Stream {
             Stream::Tcp(tcp_stream) => tcp_stream.is_write_vectored(),
             #[cfg(unix)]
             Stream::Unix(unix_stream) => unix_stream.is_write_vectored(),
+            #[cfg(windows)]
+            Stream::PipeServer(pipe_server) => pipe_server.is_write_vectored(),
+            #[cfg(windows)]
+            Stream::PipeClient(pipe_client) => pipe_client.is_write_vectored(),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(vsock_stream) => vsock_stream.is_write_vectored(),
         }
     }
 
@@ -116,6 +1242,12 @@ impl AsyncWrite for Stream {
             Stream::Tcp(tcp_stream) => Pin::new(tcp_stream).poll_flush(context),
             #[cfg(unix)]
             Stream::Unix(unix_stream) => Pin::new(unix_stream).poll_flush(context),
+            #[cfg(windows)]
+            Stream::PipeServer(pipe_server) => Pin::new(pipe_server).poll_flush(context),
+            #[cfg(windows)]
+            Stream::PipeClient(pipe_client) => Pin::new(pipe_client).poll_flush(context),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(vsock_stream) => Pin::new(vsock_stream).poll_flush(context),
         }
     }
 
@@ -124,6 +1256,734 @@ impl AsyncWrite for Stream {
             Stream::Tcp(tcp_stream) => Pin::new(tcp_stream).poll_shutdown(context),
             #[cfg(unix)]
             Stream::Unix(unix_stream) => Pin::new(unix_stream).poll_shutdown(context),
+            #[cfg(windows)]
+            Stream::PipeServer(pipe_server) => Pin::new(pipe_server).poll_shutdown(context),
+            #[cfg(windows)]
+            Stream::PipeClient(pipe_client) => Pin::new(pipe_client).poll_shutdown(context),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Stream::Vsock(vsock_stream) => Pin::new(vsock_stream).poll_shutdown(context),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_util, Listener};
+
+    #[tokio::test]
+    async fn tcp_accessors() {
+        let (mut client, _server) = test_util::tcp_pair().await;
+
+        assert!(client.is_tcp());
+        assert!(!client.is_unix());
+        assert_eq!(client.transport(), Transport::Tcp);
+        assert!(client.as_tcp().is_some());
+        assert!(client.as_tcp_mut().is_some());
+        #[cfg(unix)]
+        assert!(client.as_unix().is_none());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn unix_accessors() {
+        let (mut client, _server) = test_util::unix_pair();
+
+        assert!(client.is_unix());
+        assert!(!client.is_tcp());
+        assert_eq!(client.transport(), Transport::Unix);
+        assert!(client.as_unix().is_some());
+        assert!(client.as_unix_mut().is_some());
+        assert!(client.as_tcp().is_none());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn pass_fds() {
+        use std::io::{Read, Write};
+
+        let (a, b) = test_util::unix_pair();
+        let (mut local, remote) = unix::net::UnixStream::pair().unwrap();
+
+        assert_eq!(a.send_with_fds(b"fd", &[remote.as_raw_fd()]).await.unwrap(), 2);
+        drop(remote);
+
+        let mut buf = [0; 4];
+        let mut fds = Vec::new();
+
+        assert_eq!(b.recv_with_fds(&mut buf, &mut fds).await.unwrap(), 2);
+        assert_eq!(&buf[..2], b"fd");
+        assert_eq!(fds.len(), 1);
+
+        let mut received = unsafe { unix::net::UnixStream::from_raw_fd(fds[0]) };
+
+        received.write_all(b"hello").unwrap();
+        local.read_exact(&mut buf[..4]).unwrap();
+        assert_eq!(&buf[..4], b"hell");
+
+        assert_eq!(a.send_with_fds(b"x", &[0; 254]).await.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+
+        let (tcp, _server) = test_util::tcp_pair().await;
+
+        assert_eq!(tcp.send_with_fds(b"x", &[]).await.unwrap_err().kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[tokio::test]
+    async fn connect_any_falls_back() {
+        let (listener, local_addr) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+        let named_socket_addrs = [test_util::unused_tcp_addr().await, local_addr.clone().to_named_socket_addr().unwrap()];
+
+        let (client, accepted) = tokio::join!(Stream::connect_any(&named_socket_addrs), listener.accept());
+
+        assert_eq!(client.unwrap().peer_addr().unwrap(), local_addr);
+        accepted.unwrap();
+    }
+
+    #[tokio::test]
+    async fn connect_any_errors() {
+        let error = Stream::connect_any(&[]).await.unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+
+        let error = Stream::connect_any(&[test_util::unused_tcp_addr().await]).await.unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::ConnectionRefused);
+
+        let named_socket_addrs = [test_util::unused_tcp_addr().await, test_util::unused_tcp_addr().await];
+        let error = Stream::connect_any(&named_socket_addrs).await.unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::ConnectionRefused);
+        assert!(error.to_string().contains(&named_socket_addrs[0].to_string()));
+        assert!(error.to_string().contains(&named_socket_addrs[1].to_string()));
+    }
+
+    #[tokio::test]
+    async fn connect_from() {
+        let (listener, local_addr) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+        let named_socket_addr = local_addr.to_named_socket_addr().unwrap();
+        let bind_addr = net::SocketAddr::new(test_util::LOCALHOST, 0);
+
+        let (client, accepted) = tokio::join!(Stream::connect_from(bind_addr, &named_socket_addr), listener.accept());
+        let (client, (_, peer_addr)) = (client.unwrap(), accepted.unwrap());
+
+        assert_eq!(client.local_addr().unwrap(), peer_addr);
+
+        #[cfg(unix)]
+        {
+            let unix_socket_addr = NamedSocketAddr::unix(test_util::temp_path("connect_from.sock"));
+            let error = Stream::connect_from(bind_addr, &unix_socket_addr).await.unwrap_err();
+
+            assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+        }
+    }
+
+    #[tokio::test]
+    async fn std_round_trip() {
+        use std::io::{Read, Write};
+
+        let (client, mut server) = test_util::tcp_pair().await;
+        let local_addr = client.local_addr().unwrap();
+
+        #[cfg_attr(windows, allow(irrefutable_let_patterns))]
+        let StdStream::Tcp(mut std_stream) = client.into_std().unwrap() else { panic!("expected a tcp stream") };
+
+        // NOTE: The socket is still nonblocking, so nothing to read yet fails with WouldBlock.
+        assert_eq!(std_stream.read(&mut [0; 1]).unwrap_err().kind(), io::ErrorKind::WouldBlock);
+        std_stream.write_all(b"std").unwrap();
+
+        let mut buf = [0; 3];
+        server.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"std");
+
+        let client = Stream::from_std(StdStream::Tcp(std_stream)).unwrap();
+
+        assert_eq!(client.local_addr().unwrap(), local_addr);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn raw_fd_round_trip() {
+        let (tcp_client, _tcp_server) = test_util::tcp_pair().await;
+        let (unix_client, _unix_server) = test_util::unix_pair();
+
+        for client in [tcp_client, unix_client] {
+            let (transport, local_addr) = (client.transport(), client.local_addr().unwrap());
+            let client = unsafe { Stream::from_raw_socket_fd(client.into_std().unwrap().into_raw_fd()) }.unwrap();
+
+            assert_eq!(client.transport(), transport);
+            assert_eq!(client.local_addr().unwrap(), local_addr);
+        }
+    }
+
+    #[tokio::test]
+    async fn buffer_sizes() {
+        let (client, _server) = test_util::tcp_pair().await;
+
+        client.set_recv_buffer_size(64 * 1024).unwrap();
+        client.set_send_buffer_size(64 * 1024).unwrap();
+
+        // NOTE: Linux doubles the requested size to leave room for bookkeeping.
+        assert!(client.recv_buffer_size().unwrap() >= 64 * 1024);
+        assert!(client.send_buffer_size().unwrap() >= 64 * 1024);
+    }
+
+    #[tokio::test]
+    async fn connect_retry_gives_up() {
+        let named_socket_addr = test_util::unused_tcp_addr().await;
+        let error = Stream::connect_retry(&named_socket_addr, 2, Duration::from_millis(1)).await.unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::ConnectionRefused);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn connect_retry_waits_for_the_server() {
+        let named_socket_addr = NamedSocketAddr::unix(test_util::temp_path("retry.sock"));
+
+        let server = tokio::spawn({
+            let named_socket_addr = named_socket_addr.clone();
+
+            async move {
+                time::sleep(Duration::from_millis(30)).await;
+
+                let listener = Listener::bind(&named_socket_addr).await.unwrap();
+
+                listener.accept().await.unwrap();
+                listener.close().unwrap();
+            }
+        });
+
+        assert_eq!(Stream::connect(&named_socket_addr).await.unwrap_err().kind(), io::ErrorKind::NotFound);
+
+        Stream::connect_retry(&named_socket_addr, 20, Duration::from_millis(5)).await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn traces_accept_and_connect() {
+        let (listener, local_addr) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+        let (_client, server) = test_util::connect_to(&listener).await;
+        let peer_addr = server.peer_addr().unwrap();
+        let unused_addr = test_util::unused_tcp_addr().await;
+
+        Stream::connect(&unused_addr).await.unwrap_err();
+
+        // NOTE: Spans are formatted as name{fields} in front of the events inside them.
+        assert!(logs_contain(&format!("connect{{addr={}}}: tokio_unix_tcp::stream: Connected addr={} transport=tcp", local_addr, local_addr)));
+        assert!(logs_contain(&format!("accept{{transport=tcp}}: tokio_unix_tcp::listener: Accepted connection transport=tcp peer_addr={}", peer_addr)));
+        assert!(logs_contain(&format!("connect{{addr={}}}: tokio_unix_tcp::stream: Cannot connect addr={}", unused_addr, unused_addr)));
+    }
+
+    #[tokio::test]
+    async fn with_socket2() {
+        let (client, _server) = test_util::tcp_pair().await;
+
+        client.with_socket2(|socket| socket.set_nodelay(true)).unwrap();
+        assert!(client.with_socket2(|socket| socket.nodelay()).unwrap());
+        assert!(client.as_tcp().unwrap().nodelay().unwrap());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn connect_inherited_fd() {
+        let (client, mut server) = test_util::tcp_pair().await;
+        let mut inherited = Stream::connect(&NamedSocketAddr::from_fd(client.as_raw_fd())).await.unwrap();
+
+        assert_eq!(inherited.local_addr().unwrap(), client.local_addr().unwrap());
+
+        inherited.write_all(b"dup").await.unwrap();
+
+        let mut buf = [0; 3];
+        server.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"dup");
+
+        let (listener, _) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+        let error = Stream::connect(&NamedSocketAddr::from_fd(listener.as_raw_fd())).await.unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[tokio::test]
+    async fn write_all_vectored() {
+        let (mut a, mut b) = test_util::tcp_pair().await;
+        // NOTE: Larger than the socket buffers, which forces partial writes ending within a buffer.
+        let large = vec![b' '; 4 * 1024 * 1024];
+        let mut bufs = [IoSlice::new(b"hello"), IoSlice::new(b""), IoSlice::new(&large), IoSlice::new(b"world")];
+        let mut received = Vec::new();
+
+        let (written, read) = tokio::join!(
+            async {
+                a.write_all_vectored(&mut bufs).await?;
+                a.shutdown().await
+            },
+            b.read_to_end(&mut received),
+        );
+
+        written.unwrap();
+        assert_eq!(read.unwrap(), large.len() + 10);
+        assert_eq!(received, [&b"hello"[..], &large, b"world"].concat());
+
+        a.write_all_vectored(&mut []).await.unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn set_cloexec() {
+        let (client, _server) = test_util::tcp_pair().await;
+        let fd = client.as_raw_fd();
+
+        assert!(test_util::is_cloexec(fd));
+
+        client.set_cloexec(false).unwrap();
+        assert!(!test_util::is_cloexec(fd));
+
+        client.set_cloexec(true).unwrap();
+        assert!(test_util::is_cloexec(fd));
+    }
+
+    #[tokio::test]
+    async fn copy_bidirectional_splice() {
+        // NOTE: Spliced on Linux, copied through userspace everywhere else.
+        let (mut client, mut a) = test_util::tcp_pair().await;
+        let (mut b, mut upstream) = test_util::tcp_pair().await;
+
+        let proxy = tokio::spawn(async move { Stream::copy_bidirectional_splice(&mut a, &mut b).await });
+
+        client.write_all(b"ping").await.unwrap();
+        client.shutdown().await.unwrap();
+        upstream.write_all(b"pong!").await.unwrap();
+        upstream.shutdown().await.unwrap();
+
+        let (mut from_client, mut from_upstream) = (Vec::new(), Vec::new());
+
+        upstream.read_to_end(&mut from_client).await.unwrap();
+        client.read_to_end(&mut from_upstream).await.unwrap();
+
+        assert_eq!((from_client.as_slice(), from_upstream.as_slice()), (&b"ping"[..], &b"pong!"[..]));
+        assert_eq!(proxy.await.unwrap().unwrap(), (4, 5));
+    }
+
+    #[tokio::test]
+    async fn readiness_timeouts() {
+        let (mut client, mut server) = test_util::tcp_pair().await;
+        let timeout = Duration::from_millis(10);
+
+        assert!(!client.readable_timeout(timeout).await.unwrap());
+        assert!(client.writable_timeout(timeout).await.unwrap());
+        assert_eq!(client.read_timeout(&mut [0; 4], timeout).await.unwrap_err().kind(), io::ErrorKind::TimedOut);
+
+        server.write_all(b"data").await.unwrap();
+
+        assert!(client.readable_timeout(Duration::from_secs(5)).await.unwrap());
+        assert_eq!(client.read_timeout(&mut [0; 4], Duration::from_secs(5)).await.unwrap(), 4);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn writable_timeout_when_full() {
+        let (client, server) = test_util::unix_pair();
+        let timeout = Duration::from_millis(10);
+        let chunk = [0; 4096];
+
+        // NOTE: try_write fails with WouldBlock without writing until tokio saw the socket
+        //       become writable.
+        client.writable().await.unwrap();
+
+        loop {
+            match client.try_write(&chunk) {
+                Ok(_) => continue,
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+                Err(error) => panic!("unexpected write error: {}", error),
+            }
+        }
+
+        assert!(!client.writable_timeout(timeout).await.unwrap());
+
+        let mut buf = [0; 4096];
+
+        server.readable().await.unwrap();
+
+        loop {
+            match server.try_read(&mut buf) {
+                Ok(_) => continue,
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+                Err(error) => panic!("unexpected read error: {}", error),
+            }
+        }
+
+        assert!(client.writable_timeout(Duration::from_secs(5)).await.unwrap());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn set_mark() {
+        let (client, _server) = test_util::tcp_pair().await;
+
+        // NOTE: Setting a mark requires CAP_NET_ADMIN.
+        match client.set_mark(42) {
+            Err(error) if error.kind() == io::ErrorKind::PermissionDenied => return,
+            result => result.unwrap(),
+        }
+
+        assert_eq!(client.mark().unwrap(), 42);
+    }
+
+    #[tokio::test]
+    async fn half_close() {
+        let (mut client, mut server) = test_util::tcp_pair().await;
+
+        assert!(!server.is_read_closed().unwrap());
+
+        client.write_all(b"data").await.unwrap();
+        client.shutdown().await.unwrap();
+
+        // NOTE: The end of the stream can only be seen after reading the pending data.
+        assert!(!server.peer_closed().await.unwrap());
+        assert!(!server.is_read_closed().unwrap());
+
+        server.read_exact(&mut [0; 4]).await.unwrap();
+
+        assert!(server.peer_closed().await.unwrap());
+        assert!(server.is_read_closed().unwrap());
+        assert!(!client.is_read_closed().unwrap());
+    }
+
+    #[tokio::test]
+    async fn drain_and_shutdown() {
+        let (mut client, mut server) = test_util::tcp_pair().await;
+
+        client.write_all(&[0; 8192]).await.unwrap();
+
+        let (drained, read) = tokio::join!(server.drain_and_shutdown(usize::MAX, Duration::from_secs(5)), async {
+            let mut buf = Vec::new();
+
+            // NOTE: The server shutting down its write side lets the client see the end of
+            //       the stream, closing the client lets the drain finish before the timeout.
+            let read = client.read_to_end(&mut buf).await;
+            drop(client);
+
+            read
+        });
+
+        drained.unwrap();
+        assert_eq!(read.unwrap(), 0);
+
+        // NOTE: A peer that never closes is only drained until the timeout.
+        let (_client, mut server) = test_util::tcp_pair().await;
+
+        server.drain_and_shutdown(usize::MAX, Duration::from_millis(10)).await.unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn original_dst() {
+        let (client, _server) = test_util::tcp_pair().await;
+
+        // NOTE: Connections aren't redirected without netfilter rules, the error depends on
+        //       whether the conntrack module is loaded.
+        let error = client.original_dst().unwrap_err();
+
+        assert!(matches!(error.kind(), io::ErrorKind::Unsupported) || error.raw_os_error() == Some(libc::ENOPROTOOPT), "{:?}", error);
+        assert_eq!(test_util::unix_pair().0.original_dst().unwrap_err().kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn set_nonblocking() {
+        let (mut client, mut server) = test_util::tcp_pair().await;
+        let fd = client.as_raw_fd();
+
+        assert!(test_util::is_nonblocking(fd));
+
+        // NOTE: Restored before the stream is used with the reactor again.
+        client.set_nonblocking(false).unwrap();
+        assert!(!test_util::is_nonblocking(fd));
+
+        client.set_nonblocking(true).unwrap();
+        assert!(test_util::is_nonblocking(fd));
+
+        server.write_all(b"data").await.unwrap();
+        client.read_exact(&mut [0; 4]).await.unwrap();
+
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn try_clone() {
+        let (client, mut server) = test_util::tcp_pair().await;
+        let mut cloned = client.try_clone().unwrap();
+
+        assert_ne!(cloned.as_raw_fd(), client.as_raw_fd());
+        assert!(test_util::is_cloexec(cloned.as_raw_fd()));
+        assert_eq!(cloned.local_addr().unwrap(), client.local_addr().unwrap());
+
+        // NOTE: The duplicate keeps the connection open after the original is dropped.
+        drop(client);
+
+        cloned.write_all(b"data").await.unwrap();
+        server.read_exact(&mut [0; 4]).await.unwrap();
+
+        let (a, _b) = test_util::unix_pair();
+
+        assert!(a.try_clone().unwrap().is_unix());
+    }
+
+    #[tokio::test]
+    async fn same_connection() {
+        let (client, server) = test_util::tcp_pair().await;
+        let (other_client, _other_server) = test_util::tcp_pair().await;
+
+        assert!(client.same_connection(&client));
+        assert!(!client.same_connection(&other_client));
+
+        #[cfg(unix)]
+        {
+            // NOTE: Both ends of a connection are different sockets.
+            assert!(!client.same_connection(&server));
+            assert!(client.same_connection(&client.try_clone().unwrap()));
+
+            let (a, b) = test_util::unix_pair();
+
+            assert!(a.same_connection(&a.try_clone().unwrap()));
+            assert!(!a.same_connection(&b));
+            assert!(!a.same_connection(&client));
+        }
+
+        #[cfg(not(unix))]
+        drop(server);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn connect_autobind() {
+        let path = test_util::temp_path("autobind.sock");
+        let named_socket_addr = NamedSocketAddr::unix(&path);
+        let listener = Listener::bind(&named_socket_addr).await.unwrap();
+
+        let (client, accepted) = tokio::join!(Stream::connect_autobind(&named_socket_addr), listener.accept());
+        let (client, (_server, socket_addr)) = (client.unwrap(), accepted.unwrap());
+
+        assert!(matches!(client.local_addr().unwrap(), SocketAddr::Unix(UnixSocketAddr::Abstract(_))));
+        assert_eq!(socket_addr, client.local_addr().unwrap());
+
+        // NOTE: TCP addresses are connected to like using connect.
+        let (tcp_listener, local_addr) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+        let named_socket_addr = local_addr.to_named_socket_addr().unwrap();
+        let (client, accepted) = tokio::join!(Stream::connect_autobind(&named_socket_addr), tcp_listener.accept());
+
+        assert_eq!(accepted.unwrap().1, client.unwrap().local_addr().unwrap());
+    }
+
+    #[tokio::test]
+    async fn read_timeout_keeps_data() {
+        let (mut client, mut server) = test_util::tcp_pair().await;
+        let timeout = Duration::from_millis(10);
+        let mut buf = [0; 4];
+
+        assert_eq!(client.read_timeout(&mut buf, timeout).await.unwrap_err().kind(), io::ErrorKind::TimedOut);
+
+        // NOTE: A timed out read doesn't consume data arriving afterwards.
+        server.write_all(b"data").await.unwrap();
+        drop(server);
+
+        assert_eq!(client.read_timeout(&mut buf, Duration::from_secs(5)).await.unwrap(), 4);
+        assert_eq!(&buf, b"data");
+        assert_eq!(client.read_timeout(&mut buf, Duration::from_secs(5)).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn poll_ready_and_try_io() {
+        let (client, server) = test_util::tcp_pair().await;
+
+        future::poll_fn(|cx| client.poll_write_ready(cx)).await.unwrap();
+        assert_eq!(client.try_write(b"data").unwrap(), 4);
+
+        let mut buf = [0; 8];
+
+        // NOTE: Readiness can be stale, try_read reports that using io::ErrorKind::WouldBlock.
+        let read = loop {
+            future::poll_fn(|cx| server.poll_read_ready(cx)).await.unwrap();
+
+            match server.try_read(&mut buf) {
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => continue,
+                result => break result.unwrap(),
+            }
+        };
+
+        assert_eq!(&buf[..read], b"data");
+        assert_eq!(server.try_read(&mut buf).unwrap_err().kind(), io::ErrorKind::WouldBlock);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn queued_bytes() {
+        let (mut a, mut b) = test_util::unix_pair();
+
+        a.write_all(&[0; 100]).await.unwrap();
+
+        // NOTE: For Unix streams, sent data stays queued until the peer read it. The kernel
+        //       counts the memory used for it, which includes its bookkeeping overhead.
+        assert!(a.queued_bytes().unwrap().unsent >= 100);
+        assert_eq!(b.queued_bytes().unwrap(), QueuedBytes { unsent: 0, unread: 100 });
+
+        b.read_exact(&mut [0; 100]).await.unwrap();
+        assert_eq!(a.queued_bytes().unwrap().unsent, 0);
+
+        let (mut client, server) = test_util::tcp_pair().await;
+
+        client.write_all(b"data").await.unwrap();
+        server.readable().await.unwrap();
+        assert_eq!(server.queued_bytes().unwrap().unread, 4);
+
+    }
+
+    #[tokio::test]
+    async fn close_gracefully() {
+        let (mut client, mut server) = test_util::tcp_pair().await;
+
+        client.write_all(&[0; 4096]).await.unwrap();
+        client.close_gracefully(Duration::from_secs(5)).await.unwrap();
+
+        let mut buf = Vec::new();
+
+        assert_eq!(server.read_to_end(&mut buf).await.unwrap(), 4096);
+
+        // NOTE: On Linux, data a Unix peer never reads stays queued until the timeout.
+        #[cfg(target_os = "linux")]
+        {
+            let (mut a, _b) = test_util::unix_pair();
+
+            a.write_all(b"unread").await.unwrap();
+            assert_eq!(a.close_gracefully(Duration::from_millis(30)).await.unwrap_err().kind(), io::ErrorKind::TimedOut);
         }
     }
+
+    #[tokio::test]
+    async fn framed_u32() {
+        let (mut a, mut b) = test_util::tcp_pair().await;
+
+        a.write_framed_u32(b"hello").await.unwrap();
+        a.write_framed_u32(b"").await.unwrap();
+
+        assert_eq!(b.read_framed_u32(16).await.unwrap(), b"hello");
+        assert_eq!(b.read_framed_u32(16).await.unwrap(), b"");
+
+        a.write_framed_u32(&[0; 17]).await.unwrap();
+        assert_eq!(b.read_framed_u32(16).await.unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        // NOTE: The end of the stream within a message is unexpected.
+        let (mut a, mut b) = test_util::tcp_pair().await;
+
+        a.write_all(&[0, 0, 0, 4, 1, 2]).await.unwrap();
+        drop(a);
+
+        assert_eq!(b.read_framed_u32(16).await.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn pass_creds() {
+        let (mut a, b) = test_util::unix_pair();
+        let mut buf = [0; 4];
+
+        assert!(!b.pass_cred().unwrap());
+        a.write_all(b"none").await.unwrap();
+        assert_eq!(b.recv_with_creds(&mut buf).await.unwrap(), (4, None));
+
+        b.set_pass_cred(true).unwrap();
+        assert!(b.pass_cred().unwrap());
+
+        assert_eq!(a.send_with_creds(b"sent", Credentials::current()).await.unwrap(), 4);
+        assert_eq!(b.recv_with_creds(&mut buf).await.unwrap(), (4, Some(Credentials::current())));
+
+        // NOTE: The kernel attaches the credentials even if the sender didn't.
+        a.write_all(b"auto").await.unwrap();
+        assert_eq!(b.recv_with_creds(&mut buf).await.unwrap(), (4, Some(Credentials::current())));
+
+        let (tcp, _server) = test_util::tcp_pair().await;
+
+        assert_eq!(tcp.set_pass_cred(true).unwrap_err().kind(), io::ErrorKind::Unsupported);
+        assert_eq!(tcp.send_with_creds(b"x", Credentials::current()).await.unwrap_err().kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn pass_creds_on_accepted_streams() {
+        let listener = Listener::bind(&NamedSocketAddr::unix(test_util::temp_path("pass_cred.sock"))).await.unwrap();
+        let (mut client, server) = test_util::connect_to(&listener).await;
+
+        server.set_pass_cred(true).unwrap();
+        assert!(server.pass_cred().unwrap());
+        assert!(!client.pass_cred().unwrap());
+
+        client.write_all(b"data").await.unwrap();
+        assert_eq!(server.recv_with_creds(&mut [0; 4]).await.unwrap(), (4, Some(Credentials::current())));
+
+        server.set_pass_cred(false).unwrap();
+        assert!(!server.pass_cred().unwrap());
+
+        listener.close().unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn tcp_info() {
+        let (mut client, mut server) = test_util::tcp_pair().await;
+
+        client.write_all(b"ping").await.unwrap();
+        server.read_exact(&mut [0; 4]).await.unwrap();
+
+        let tcp_info = client.tcp_info().unwrap();
+
+        assert!(tcp_info.is_established());
+        assert_eq!(tcp_info.total_retransmits, 0);
+
+        // NOTE: Once the peer closed the connection, the state moves on to CLOSE_WAIT.
+        drop(server);
+        client.peer_closed().await.unwrap();
+        assert!(!client.tcp_info().unwrap().is_established());
+
+        assert_eq!(test_util::unix_pair().0.tcp_info().unwrap_err().kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn connect_autobind_waits_for_backlog() {
+        let path = test_util::temp_path("autobind_backlog.sock");
+        let named_socket_addr = NamedSocketAddr::unix(&path);
+        let socket = Socket::new(Domain::UNIX, Type::STREAM, None).unwrap();
+
+        socket.bind(&SockAddr::unix(&path).unwrap()).unwrap();
+        socket.listen(0).unwrap();
+
+        let _pending = Stream::connect_autobind(&named_socket_addr).await.unwrap();
+        let connect = tokio::spawn(async move { Stream::connect_autobind(&named_socket_addr).await });
+
+        time::sleep(Duration::from_millis(50)).await;
+        assert!(!connect.is_finished());
+
+        socket.accept().unwrap();
+
+        time::timeout(Duration::from_secs(5), connect).await.unwrap().unwrap().unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn recv_with_creds_closes_fds() {
+        use std::io::Read;
+
+        let (a, b) = test_util::unix_pair();
+        let (mut local, remote) = unix::net::UnixStream::pair().unwrap();
+
+        a.send_with_fds(b"fd", &[remote.as_raw_fd()]).await.unwrap();
+        drop(remote);
+
+        assert_eq!(b.recv_with_creds(&mut [0; 2]).await.unwrap().0, 2);
+
+        // NOTE: Only sees the end of the stream once the received duplicate was closed too.
+        local.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        assert_eq!(local.read(&mut [0; 1]).unwrap(), 0);
+    }
 }
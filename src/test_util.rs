@@ -0,0 +1,77 @@
+/*
+ * Copyright (c) 2023, networkException <git@nwex.de>
+ *
+ * SPDX-License-Identifier: BSD-2-Clause OR MIT
+ */
+
+use std::net::{IpAddr, Ipv4Addr};
+#[cfg(unix)]
+use std::{path::PathBuf, sync::atomic::{AtomicUsize, Ordering}};
+
+use crate::{Listener, NamedSocketAddr, Stream};
+
+pub(crate) const LOCALHOST: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+// Returns a path in the temporary directory that is unique within the test binary and
+// doesn't exist yet, for binding unix sockets.
+#[cfg(unix)]
+pub(crate) fn temp_path(name: &str) -> PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+    let path = std::env::temp_dir().join(format!(
+        "tokio-unix-tcp-{}-{}-{}",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed),
+        name,
+    ));
+
+    let _ = std::fs::remove_file(&path);
+
+    path
+}
+
+#[cfg(unix)]
+pub(crate) fn is_cloexec(fd: std::os::unix::io::RawFd) -> bool {
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFD) };
+
+    assert!(flags >= 0, "Cannot get the flags of fd {}", fd);
+
+    flags & libc::FD_CLOEXEC != 0
+}
+
+#[cfg(unix)]
+pub(crate) fn is_nonblocking(fd: std::os::unix::io::RawFd) -> bool {
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+
+    assert!(flags >= 0, "Cannot get the status flags of fd {}", fd);
+
+    flags & libc::O_NONBLOCK != 0
+}
+
+// Returns the client and server side of a connection to the listener.
+pub(crate) async fn connect_to(listener: &Listener) -> (Stream, Stream) {
+    let named_socket_addr = listener.local_addr().unwrap().to_named_socket_addr().unwrap();
+    let (client, accepted) = tokio::join!(Stream::connect(&named_socket_addr), listener.accept());
+
+    (client.unwrap(), accepted.unwrap().0)
+}
+
+// Returns a local tcp address nothing listens on, connecting to it is refused.
+pub(crate) async fn unused_tcp_addr() -> NamedSocketAddr {
+    let (_, local_addr) = Listener::bind_ephemeral(LOCALHOST).await.unwrap();
+
+    local_addr.to_named_socket_addr().unwrap()
+}
+
+pub(crate) async fn tcp_pair() -> (Stream, Stream) {
+    let (listener, _) = Listener::bind_ephemeral(LOCALHOST).await.unwrap();
+
+    connect_to(&listener).await
+}
+
+#[cfg(unix)]
+pub(crate) fn unix_pair() -> (Stream, Stream) {
+    let (a, b) = tokio::net::UnixStream::pair().unwrap();
+
+    (Stream::Unix(a), Stream::Unix(b))
+}
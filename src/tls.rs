@@ -0,0 +1,200 @@
+/*
+ * Copyright (c) 2023, networkException <git@nwex.de>
+ *
+ * SPDX-License-Identifier: BSD-2-Clause OR MIT
+ */
+
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_rustls::rustls::ServerName;
+use tokio_rustls::{client, server, TlsAcceptor, TlsConnector};
+
+use crate::{Listener, NamedSocketAddr, SocketAddr, Stream};
+
+#[derive(Debug)]
+pub enum TlsStream {
+    Client(client::TlsStream<Stream>),
+    Server(server::TlsStream<Stream>),
+}
+
+impl From<client::TlsStream<Stream>> for TlsStream {
+    fn from(tls_stream: client::TlsStream<Stream>) -> Self {
+        TlsStream::Client(tls_stream)
+    }
+}
+
+impl From<server::TlsStream<Stream>> for TlsStream {
+    fn from(tls_stream: server::TlsStream<Stream>) -> Self {
+        TlsStream::Server(tls_stream)
+    }
+}
+
+impl TlsStream {
+    pub fn get_ref(&self) -> &Stream {
+        match self {
+            TlsStream::Client(tls_stream) => tls_stream.get_ref().0,
+            TlsStream::Server(tls_stream) => tls_stream.get_ref().0,
+        }
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.get_ref().local_addr()
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.get_ref().peer_addr()
+    }
+}
+
+impl Listener {
+    // NOTE: The handshake runs inline, so a slow or stalled client blocks accepting further
+    //       connections until it completes. Servers should call accept and pass the stream to
+    //       TlsAcceptor::accept in a spawned task instead, ideally with a timeout.
+    pub async fn accept_tls(&self, acceptor: &TlsAcceptor) -> io::Result<(TlsStream, SocketAddr)> {
+        let (stream, socket_addr) = self.accept().await?;
+
+        Ok((TlsStream::Server(acceptor.accept(stream).await?), socket_addr))
+    }
+}
+
+impl Stream {
+    pub async fn connect_tls(named_socket_addr: &NamedSocketAddr, connector: &TlsConnector, server_name: ServerName) -> io::Result<TlsStream> {
+        let stream = Stream::connect(named_socket_addr).await?;
+
+        Ok(TlsStream::Client(connector.connect(server_name, stream).await?))
+    }
+}
+
+impl AsyncRead for TlsStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match Pin::into_inner(self) {
+            TlsStream::Client(tls_stream) => Pin::new(tls_stream).poll_read(cx, buf),
+            TlsStream::Server(tls_stream) => Pin::new(tls_stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for TlsStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match Pin::into_inner(self) {
+            TlsStream::Client(tls_stream) => Pin::new(tls_stream).poll_write(cx, buf),
+            TlsStream::Server(tls_stream) => Pin::new(tls_stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        match Pin::into_inner(self) {
+            TlsStream::Client(tls_stream) => Pin::new(tls_stream).poll_write_vectored(cx, bufs),
+            TlsStream::Server(tls_stream) => Pin::new(tls_stream).poll_write_vectored(cx, bufs),
+        }
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        match self {
+            TlsStream::Client(tls_stream) => tls_stream.is_write_vectored(),
+            TlsStream::Server(tls_stream) => tls_stream.is_write_vectored(),
+        }
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match Pin::into_inner(self) {
+            TlsStream::Client(tls_stream) => Pin::new(tls_stream).poll_flush(context),
+            TlsStream::Server(tls_stream) => Pin::new(tls_stream).poll_flush(context),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match Pin::into_inner(self) {
+            TlsStream::Client(tls_stream) => Pin::new(tls_stream).poll_shutdown(context),
+            TlsStream::Server(tls_stream) => Pin::new(tls_stream).poll_shutdown(context),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio_rustls::rustls::{Certificate, ClientConfig, PrivateKey, RootCertStore, ServerConfig};
+
+    use super::*;
+    use crate::test_util;
+
+    fn acceptor_and_connector() -> (TlsAcceptor, TlsConnector) {
+        let certificate = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let der = Certificate(certificate.serialize_der().unwrap());
+
+        let server_config = ServerConfig::builder()
+            .with_safe_defaults()
+            .with_no_client_auth()
+            .with_single_cert(vec![der.clone()], PrivateKey(certificate.serialize_private_key_der()))
+            .unwrap();
+
+        let mut roots = RootCertStore::empty();
+        roots.add(&der).unwrap();
+
+        let client_config = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+
+        (TlsAcceptor::from(Arc::new(server_config)), TlsConnector::from(Arc::new(client_config)))
+    }
+
+    async fn handshake(listener: &Listener) {
+        let (acceptor, connector) = acceptor_and_connector();
+        let named_socket_addr = listener.local_addr().unwrap().to_named_socket_addr().unwrap();
+        let server_name = ServerName::try_from("localhost").unwrap();
+
+        let (client, accepted) = tokio::join!(
+            Stream::connect_tls(&named_socket_addr, &connector, server_name),
+            listener.accept_tls(&acceptor),
+        );
+
+        let (mut client, (mut server, _)) = (client.unwrap(), accepted.unwrap());
+
+        assert!(matches!(client, TlsStream::Client(_)));
+        assert!(matches!(server, TlsStream::Server(_)));
+        assert_eq!(client.peer_addr().unwrap(), server.local_addr().unwrap());
+
+        client.write_all(b"ping").await.unwrap();
+        client.flush().await.unwrap();
+
+        let mut buf = [0; 4];
+        server.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ping");
+    }
+
+    #[tokio::test]
+    async fn tcp_handshake() {
+        let (listener, _) = Listener::bind_ephemeral(test_util::LOCALHOST).await.unwrap();
+
+        handshake(&listener).await;
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn unix_handshake() {
+        let listener = Listener::bind(&NamedSocketAddr::unix(test_util::temp_path("tls.sock"))).await.unwrap();
+
+        handshake(&listener).await;
+        listener.close().unwrap();
+    }
+}
@@ -0,0 +1,55 @@
+/*
+ * Copyright (c) 2023, networkException <git@nwex.de>
+ *
+ * SPDX-License-Identifier: BSD-2-Clause OR MIT
+ */
+
+use std::fmt;
+
+// NOTE: Unlike the variants of Stream and Listener, Unix is available on every
+//       platform so labels can be matched on without cfg attributes. Still non
+//       exhaustive as the remaining variants depend on the platform and features.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Transport {
+    Tcp,
+    Unix,
+    #[cfg(windows)]
+    Pipe,
+    #[cfg(all(feature = "vsock", target_os = "linux"))]
+    Vsock,
+}
+
+impl Transport {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Transport::Tcp => "tcp",
+            Transport::Unix => "unix",
+            #[cfg(windows)]
+            Transport::Pipe => "pipe",
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Transport::Vsock => "vsock",
+        }
+    }
+}
+
+impl fmt::Display for Transport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SocketAddr;
+
+    #[test]
+    fn labels() {
+        assert_eq!(Transport::Tcp.to_string(), "tcp");
+        assert_eq!(Transport::Unix.as_str(), "unix");
+
+        assert_eq!("127.0.0.1:80".parse::<SocketAddr>().unwrap().transport(), Transport::Tcp);
+        assert_eq!(SocketAddr::unix("/run/app.sock").transport(), Transport::Unix);
+    }
+}
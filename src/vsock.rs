@@ -0,0 +1,151 @@
+/*
+ * Copyright (c) 2023, networkException <git@nwex.de>
+ *
+ * SPDX-License-Identifier: BSD-2-Clause OR MIT
+ */
+
+use std::io;
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+use std::task::{ready, Context, Poll};
+
+use tokio::io::unix::AsyncFd;
+use tokio_vsock::{VsockAddr, VsockStream};
+
+// NOTE: tokio_vsock::VsockListener requires mutable access for accepting, which
+//       doesn't fit Listener::accept taking &self.
+#[derive(Debug)]
+pub struct VsockListener {
+    inner: AsyncFd<vsock::VsockListener>,
+}
+
+impl VsockListener {
+    pub fn bind(cid: u32, port: u32) -> io::Result<VsockListener> {
+        VsockListener::from_std(vsock::VsockListener::bind_with_cid_port(cid, port)?)
+    }
+
+    fn from_std(listener: vsock::VsockListener) -> io::Result<VsockListener> {
+        listener.set_nonblocking(true)?;
+
+        Ok(VsockListener {
+            inner: AsyncFd::new(listener)?,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<VsockAddr> {
+        self.inner.get_ref().local_addr()
+    }
+
+    // NOTE: Unlike poll_accept, this wakes every task waiting to accept.
+    pub async fn accept(&self) -> io::Result<(VsockStream, VsockAddr)> {
+        loop {
+            let mut guard = self.inner.readable().await?;
+
+            match guard.try_io(|inner| inner.get_ref().accept()) {
+                Ok(Ok((vsock_stream, vsock_addr))) => return VsockStream::new(vsock_stream).map(|vsock_stream| (vsock_stream, vsock_addr)),
+                Ok(Err(error)) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Ok(Err(error)) => return Err(error),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    pub fn poll_accept(&self, cx: &mut Context<'_>) -> Poll<io::Result<(VsockStream, VsockAddr)>> {
+        loop {
+            let mut guard = ready!(self.inner.poll_read_ready(cx))?;
+
+            match guard.try_io(|inner| inner.get_ref().accept()) {
+                Ok(Ok((vsock_stream, vsock_addr))) => return Poll::Ready(VsockStream::new(vsock_stream).map(|vsock_stream| (vsock_stream, vsock_addr))),
+                Ok(Err(error)) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Ok(Err(error)) => return Poll::Ready(Err(error)),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+}
+
+impl VsockListener {
+    /// # Safety
+    ///
+    /// The file descriptor must be an open, listening vsock socket that is not
+    /// owned by anything else, ownership is transferred to the returned listener.
+    pub unsafe fn from_raw_fd(fd: RawFd) -> io::Result<VsockListener> {
+        VsockListener::from_std(vsock::VsockListener::from_raw_fd(fd))
+    }
+}
+
+impl AsRawFd for VsockListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+impl IntoRawFd for VsockListener {
+    fn into_raw_fd(self) -> RawFd {
+        self.inner.into_inner().into_raw_fd()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use crate::{Listener, NamedSocketAddr, SocketAddr, Stream, Transport};
+
+    #[test]
+    fn parse_and_display() {
+        let named_socket_addr: NamedSocketAddr = "vsock:2:1234".parse().unwrap();
+
+        assert_eq!(named_socket_addr, NamedSocketAddr::Vsock { cid: 2, port: 1234 });
+        assert_eq!(named_socket_addr.to_string(), "vsock:2:1234");
+        assert_eq!(SocketAddr::from(named_socket_addr).transport(), Transport::Vsock);
+        assert!("vsock:2".parse::<NamedSocketAddr>().is_err());
+        assert!("vsock:host:1234".parse::<NamedSocketAddr>().is_err());
+    }
+
+    // NOTE: Only a missing vsock kernel module skips a test, any other error fails it.
+    fn unavailable(error: &io::Error) -> bool {
+        let unavailable = matches!(error.raw_os_error(), Some(libc::ENODEV | libc::EAFNOSUPPORT));
+
+        if unavailable {
+            eprintln!("skipping, vsock is not available: {}", error);
+        }
+
+        unavailable
+    }
+
+    #[tokio::test]
+    async fn bind_any_cid() {
+        let listener = match Listener::bind(&NamedSocketAddr::Vsock { cid: libc::VMADDR_CID_ANY, port: 41234 }).await {
+            Err(error) if unavailable(&error) => return,
+            result => result.unwrap(),
+        };
+
+        assert_eq!(listener.transport(), Transport::Vsock);
+        assert_eq!(listener.local_addr().unwrap(), SocketAddr::Vsock { cid: libc::VMADDR_CID_ANY, port: 41234 });
+    }
+
+    // NOTE: Binding VMADDR_CID_LOCAL fails with AddrNotAvailable unless the loopback transport
+    //       is loaded, run using cargo test --features vsock -- --ignored.
+    #[tokio::test]
+    #[ignore = "needs the vsock_loopback kernel module"]
+    async fn local_round_trip() {
+        let named_socket_addr = NamedSocketAddr::Vsock { cid: libc::VMADDR_CID_LOCAL, port: 41235 };
+        let listener = Listener::bind(&named_socket_addr).await.unwrap();
+
+        let (client, accepted) = tokio::join!(Stream::connect(&named_socket_addr), listener.accept());
+        let (mut client, (mut server, peer_addr)) = (client.unwrap(), accepted.unwrap());
+
+        assert_eq!(client.transport(), Transport::Vsock);
+        assert_eq!(client.peer_addr().unwrap(), SocketAddr::from(named_socket_addr));
+        assert!(matches!(peer_addr, SocketAddr::Vsock { cid: libc::VMADDR_CID_LOCAL, .. }));
+
+        client.write_all(b"ping").await.unwrap();
+
+        let mut buf = [0; 4];
+        server.read_exact(&mut buf).await.unwrap();
+
+        assert_eq!(&buf, b"ping");
+    }
+}